@@ -0,0 +1,131 @@
+//! Configure [`Frame`] beyond capacity and preserved size
+
+use bytes::BytesMut;
+
+use crate::{Frame, FrameError};
+
+/// Builder of [`Frame`] with tunable fill policy
+///
+/// Default policy is same as [`Frame::new`], fill return once it read at least twice of preserved size
+/// or buffer is full, buffer is allocated upfront.
+pub struct FrameBuilder {
+	capacity: usize,
+	preserved: usize,
+	max_ratio: usize,
+	min_fill: Option<usize>,
+	max_reads: usize,
+	initial_capacity: Option<usize>,
+	max_capacity: Option<usize>,
+	align: Option<usize>,
+}
+
+impl FrameBuilder {
+	pub fn new(capacity: usize, preserved: usize) -> Self {
+		Self { capacity, preserved, max_ratio: 4, min_fill: None, max_reads: usize::MAX, initial_capacity: None, max_capacity: None, align: None }
+	}
+
+	/// Largest allowed ratio of capacity to preserved size, `0` turn check off
+	pub fn max_ratio(mut self, max_ratio: usize) -> Self {
+		self.max_ratio = max_ratio;
+		self
+	}
+
+	/// Bytes fill has to read before it return unless buffer is full or source reach end of stream,
+	/// fill keep reading past it until buffer hold more than preserved region
+	pub fn min_fill(mut self, min_fill: usize) -> Self {
+		self.min_fill = Some(min_fill);
+		self
+	}
+
+	/// Maximum number of reads issued by single fill, at least one read is always issued,
+	/// more are issued while buffer hold no more than preserved region
+	pub fn max_reads(mut self, max_reads: usize) -> Self {
+		self.max_reads = max_reads.max(1);
+		self
+	}
+
+	/// Bytes allocated upfront, buffer grow to capacity on first fill
+	pub fn initial_capacity(mut self, initial_capacity: usize) -> Self {
+		self.initial_capacity = Some(initial_capacity);
+		self
+	}
+
+	/// Capacity frame may grow to with [`Frame::mark_incomplete`], growth is off by default
+	pub fn max_capacity(mut self, max_capacity: usize) -> Self {
+		self.max_capacity = Some(max_capacity);
+		self
+	}
+
+	/// Round capacity up to multiple of `align` and allocate buffer starting at aligned address, e.g. for `O_DIRECT` reads
+	///
+	/// Data retained by consume is shifted so read after it still start at aligned address,
+	/// readers still have to check it since short read leave it unaligned.
+	pub fn align(mut self, align: usize) -> Self {
+		self.align = Some(align);
+		self
+	}
+
+	pub fn build(self) -> Result<Frame, FrameError> {
+		let Self { mut capacity, preserved, .. } = self;
+		if let Some(align) = self.align {
+			if !align.is_power_of_two() { return Err(FrameError::Alignment { align }); }
+			capacity = capacity.div_ceil(align) * align;
+		}
+		if preserved >= capacity { return Err(FrameError::PreservedTooLarge { capacity, preserved }); }
+		if self.max_ratio > 0 && preserved < capacity / self.max_ratio { return Err(FrameError::PreservedTooSmall { capacity, preserved }); }
+		let mut frame = Frame::raw(0, preserved);
+		frame.capacity = capacity;
+		frame.buf = match self.align {
+			Some(align) => aligned(self.initial_capacity.unwrap_or(capacity), align),
+			None => BytesMut::with_capacity(self.initial_capacity.unwrap_or(capacity)),
+		};
+		frame.min_fill = self.min_fill;
+		frame.max_reads = self.max_reads;
+		frame.base_capacity = capacity;
+		frame.max_capacity = self.max_capacity.map_or(capacity, |max| max.max(capacity));
+		frame.align = self.align.unwrap_or(1);
+		frame.max_ratio = self.max_ratio;
+		Ok(frame)
+	}
+}
+
+/// Buffer of `capacity` bytes starting at multiple of `align`, padding before it is cut off
+fn aligned(capacity: usize, align: usize) -> BytesMut {
+	let mut buf = BytesMut::with_capacity(capacity + align - 1);
+	let pad = buf.as_ptr().align_offset(align);
+	buf.resize(pad, 0);
+	buf.split_off(pad)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::FrameError;
+
+	use super::FrameBuilder;
+
+	#[test]
+	fn test_builder() {
+		assert!(matches!(FrameBuilder::new(16, 2).build(), Err(FrameError::PreservedTooSmall { .. })));
+		let frame = FrameBuilder::new(16, 2).max_ratio(0).initial_capacity(4).build().unwrap();
+		assert_eq!(frame.buf.capacity(), 4);
+	}
+
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_fill_policy() {
+		use std::ops::Deref;
+
+		use crate::tests::Trickle;
+
+		let mut frame = FrameBuilder::new(8, 2).build().unwrap();
+		assert_eq!(frame.read_std(&mut Trickle(b"Hello world!", 2)).unwrap().read, 4);
+		let mut frame = FrameBuilder::new(8, 2).min_fill(8).build().unwrap();
+		assert_eq!(frame.read_std(&mut Trickle(b"Hello world!", 2)).unwrap().read, 8);
+		let mut frame = FrameBuilder::new(8, 2).min_fill(8).max_reads(3).build().unwrap();
+		assert_eq!(frame.read_std(&mut Trickle(b"Hello world!", 2)).unwrap().read, 6);
+		assert_eq!(frame.deref(), b"Hello ");
+		let mut frame = FrameBuilder::new(8, 2).min_fill(1).initial_capacity(0).build().unwrap();
+		assert_eq!(frame.read_std(&mut Trickle(b"Hello world!", 2)).unwrap().read, 4);
+		assert_eq!(frame.buf.capacity(), 8);
+	}
+}
@@ -0,0 +1,133 @@
+//! Integration with [`tokio_util::codec`]
+
+use std::io;
+
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::Decoder;
+
+#[cfg(feature = "read_std")]
+use crate::BlockingSource;
+use crate::{Frame, Source};
+
+impl Frame {
+	/// Run `decoder` on buffered data, keep absolute offset in sync with bytes it consumed
+	fn decode_buffered<D: Decoder>(&mut self, decoder: &mut D, eof: bool) -> Result<Option<D::Item>, D::Error> {
+		let len = self.buf.len();
+		let item = if eof { decoder.decode_eof(&mut self.buf) } else { decoder.decode(&mut self.buf) };
+		let consumed = len.saturating_sub(self.buf.len());
+		if consumed > 0 {
+			self.offset += consumed as u64;
+			self.overlap = 0;
+		}
+		item
+	}
+
+	/// Decode next item with `decoder` using frame buffer as decode buffer, refill it from `source` when needed
+	pub async fn decode<D, S>(&mut self, decoder: &mut D, source: &mut S) -> Result<Option<D::Item>, D::Error>
+		where D: Decoder,
+		      S: Source + ?Sized {
+		loop {
+			if let Some(item) = self.decode_buffered(decoder, false)? { return Ok(Some(item)); }
+			if self.fill(source).await?.eof { return self.decode_buffered(decoder, true); }
+		}
+	}
+
+	/// Blocking version of [`Frame::decode`]
+	#[cfg(feature = "read_std")]
+	pub fn decode_blocking<D, S>(&mut self, decoder: &mut D, source: &mut S) -> Result<Option<D::Item>, D::Error>
+		where D: Decoder,
+		      S: BlockingSource + ?Sized {
+		loop {
+			if let Some(item) = self.decode_buffered(decoder, false)? { return Ok(Some(item)); }
+			if self.fill_blocking(source)?.eof { return self.decode_buffered(decoder, true); }
+		}
+	}
+}
+
+/// [`Decoder`] which emit windows of `capacity` bytes with their absolute offset,
+/// consecutive windows overlap by `preserved` bytes like windows of [`Frame`]
+///
+/// Window is copied out of decode buffer since preserved bytes are shared with next window
+pub struct Windows {
+	capacity: usize,
+	preserved: usize,
+	offset: u64,
+	// bytes at start of decode buffer which were already part of previous window
+	overlap: usize,
+}
+
+impl Windows {
+	pub fn new(capacity: usize, preserved: usize) -> Self {
+		if capacity <= preserved { panic!("Capacity must be larger than preserved size") }
+		Self { capacity, preserved, offset: 0, overlap: 0 }
+	}
+
+	fn window(&mut self, src: &mut BytesMut, len: usize, advance: usize) -> (u64, BytesMut) {
+		let window = (self.offset, BytesMut::from(&src[..len]));
+		src.advance(advance);
+		self.offset += advance as u64;
+		self.overlap = len - advance;
+		window
+	}
+}
+
+impl Decoder for Windows {
+	type Item = (u64, BytesMut);
+	type Error = io::Error;
+
+	fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+		if src.len() < self.capacity {
+			src.reserve(self.capacity - src.len());
+			return Ok(None);
+		}
+		Ok(Some(self.window(src, self.capacity, self.capacity - self.preserved)))
+	}
+
+	fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+		if let Some(window) = self.decode(src)? { return Ok(Some(window)); }
+		// last window, unless there is nothing new after previous one
+		let len = src.len();
+		if len > self.overlap {
+			Ok(Some(self.window(src, len, len)))
+		} else {
+			src.clear();
+			Ok(None)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use bytes::BytesMut;
+	use tokio_util::codec::Decoder;
+
+	use super::Windows;
+
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_frame_decode() {
+		use tokio_util::codec::LinesCodec;
+
+		let mut frame = crate::Frame::new(8, 2);
+		let mut codec = LinesCodec::new();
+		let mut source = &b"one\ntwo\nthree"[..];
+		let mut lines = Vec::new();
+		while let Some(line) = frame.decode_blocking(&mut codec, &mut source).unwrap() {
+			lines.push(line);
+		}
+		assert_eq!(lines, ["one", "two", "three"]);
+		assert_eq!(frame.offset(), 13);
+	}
+
+	#[test]
+	fn test_windows() {
+		let mut windows = Windows::new(4, 1);
+		let mut src = BytesMut::from(&b"abcdefghi"[..]);
+		let window = |offset, bytes: &[u8]| Some((offset, BytesMut::from(bytes)));
+		assert_eq!(windows.decode(&mut src).unwrap(), window(0, b"abcd"));
+		assert_eq!(windows.decode(&mut src).unwrap(), window(3, b"defg"));
+		assert_eq!(windows.decode(&mut src).unwrap(), None);
+		assert_eq!(windows.decode_eof(&mut src).unwrap(), window(6, b"ghi"));
+		assert_eq!(windows.decode_eof(&mut src).unwrap(), None);
+	}
+}
@@ -0,0 +1,155 @@
+//! Positional reader of files opened with `O_DIRECT`, bypassing page cache
+
+use std::alloc::Layout;
+use std::fs::File;
+use std::io;
+use std::os::unix::fs::FileExt;
+
+use bytes::BytesMut;
+
+use crate::BlockingSource;
+
+/// Blocks read into bounce buffer at once
+const BOUNCE_BLOCKS: usize = 16;
+
+/// Block aligned buffer for reads which can't go straight into frame
+struct Bounce {
+	ptr: *mut u8,
+	layout: Layout,
+}
+
+impl Bounce {
+	fn new(block: usize) -> io::Result<Self> {
+		let layout = Layout::from_size_align(block * BOUNCE_BLOCKS, block)
+			.map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+		let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+		if ptr.is_null() { std::alloc::handle_alloc_error(layout) }
+		Ok(Self { ptr, layout })
+	}
+
+	fn as_mut(&mut self) -> &mut [u8] {
+		unsafe { std::slice::from_raw_parts_mut(self.ptr, self.layout.size()) }
+	}
+}
+
+impl Drop for Bounce {
+	fn drop(&mut self) {
+		unsafe { std::alloc::dealloc(self.ptr, self.layout) }
+	}
+}
+
+// bounce buffer is owned by reader
+unsafe impl Send for Bounce {}
+
+/// [`BlockingSource`] which read file at its own position with block aligned offset, length and memory
+///
+/// When spare capacity of frame and position are aligned, data is read straight into frame.
+/// Otherwise, e.g. behind unaligned preserved region, aligned blocks are read into bounce buffer and copied.
+/// Final short block is cut at end of file.
+pub struct Direct {
+	file: File,
+	block: usize,
+	pos: u64,
+	bounce: Bounce,
+	// file position and length of data held in bounce buffer
+	bounce_pos: u64,
+	bounce_len: usize,
+}
+
+impl Direct {
+	/// Open `path` with `O_DIRECT` and start reading at `pos`, `block` is alignment required by device
+	pub fn open(path: impl AsRef<std::path::Path>, block: usize, pos: u64) -> io::Result<Self> {
+		use std::os::unix::fs::OpenOptionsExt;
+		let file = std::fs::OpenOptions::new().read(true).custom_flags(libc::O_DIRECT).open(path)?;
+		Self::new(file, block, pos)
+	}
+
+	/// Read already opened `file`, it's expected to be opened with `O_DIRECT`
+	pub fn new(file: File, block: usize, pos: u64) -> io::Result<Self> {
+		if !block.is_power_of_two() {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("block size {} is not power of two", block)));
+		}
+		Ok(Self { file, block, pos, bounce: Bounce::new(block)?, bounce_pos: 0, bounce_len: 0 })
+	}
+
+	/// Absolute position of next read
+	pub fn position(&self) -> u64 {
+		self.pos
+	}
+
+	/// Part of bounce buffer at current position
+	fn buffered(&self) -> &[u8] {
+		let end = self.bounce_pos + self.bounce_len as u64;
+		if self.pos < self.bounce_pos || self.pos >= end { return &[]; }
+		let start = (self.pos - self.bounce_pos) as usize;
+		unsafe { std::slice::from_raw_parts(self.bounce.ptr.add(start), self.bounce_len - start) }
+	}
+
+	/// Read blocks around current position into bounce buffer
+	fn refill(&mut self) -> io::Result<()> {
+		let pos = self.pos & !(self.block as u64 - 1);
+		self.bounce_len = 0;
+		self.bounce_len = self.file.read_at(self.bounce.as_mut(), pos)?;
+		self.bounce_pos = pos;
+		Ok(())
+	}
+}
+
+impl BlockingSource for Direct {
+	fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+		let len = buf.len();
+		let spare = buf.capacity() - len;
+		if self.buffered().is_empty() {
+			let aligned = (self.pos as usize | (buf.as_ptr() as usize + len)) & (self.block - 1) == 0;
+			if aligned && spare >= self.block {
+				buf.resize(len + spare / self.block * self.block, 0);
+				let res = self.file.read_at(&mut buf[len..], self.pos);
+				buf.truncate(len + res.as_ref().copied().unwrap_or(0));
+				self.pos += buf.len() as u64 - len as u64;
+				return res;
+			}
+			self.refill()?;
+		}
+		let src = self.buffered();
+		let n = src.len().min(spare);
+		buf.extend_from_slice(&src[..n]);
+		self.pos += n as u64;
+		Ok(n)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::FrameBuilder;
+
+	use super::Direct;
+
+	#[test]
+	fn test_direct() {
+		let data: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+		let path = crate::tests::temp_path("direct");
+		std::fs::write(&path, &data).unwrap();
+		// page cache is still used since tmpfs doesn't support O_DIRECT, but reads stay aligned
+		for start in [0, 100] {
+			let mut frame = FrameBuilder::new(1000, 256).align(512).build().unwrap();
+			assert_eq!(frame.buf.as_ptr() as usize % 512, 0);
+			assert_eq!(frame.capacity, 1024);
+			frame.set_offset(start);
+			let mut source = Direct::new(std::fs::File::open(&path).unwrap(), 512, start).unwrap();
+			let mut read = Vec::new();
+			loop {
+				let outcome = frame.fill_blocking(&mut source).unwrap();
+				if outcome.eof { break; }
+				// frame keep write pointer aligned after consume, so only tail of file go through bounce buffer
+				if start == 0 { assert_eq!(source.bounce_len, 0); }
+				let (offset, bytes) = frame.consume();
+				assert_eq!(offset, start + read.len() as u64);
+				read.extend_from_slice(&bytes);
+			}
+			read.extend_from_slice(&frame);
+			assert_eq!(read, &data[start as usize..]);
+			assert_eq!(source.position(), data.len() as u64);
+		}
+		std::fs::remove_file(path).unwrap();
+	}
+}
@@ -0,0 +1,82 @@
+//! Blocking [`Iterator`] of consumed windows of [`Frame`]
+
+use std::io;
+
+use crate::{BlockingSource, Frame, Window};
+
+/// Fill [`Frame`] from blocking source and yield consumed [`Window`]s,
+/// last window contain everything left in frame when source reach end of stream
+pub struct FrameIter<S> {
+	frame: Frame,
+	source: S,
+	done: bool,
+}
+
+impl<S: BlockingSource> FrameIter<S> {
+	pub fn new(source: S, capacity: usize, preserved: usize) -> Self {
+		Self::with_frame(Frame::new(capacity, preserved), source)
+	}
+
+	pub fn with_frame(frame: Frame, source: S) -> Self {
+		Self { frame, source, done: false }
+	}
+
+	pub fn into_inner(self) -> (Frame, S) {
+		(self.frame, self.source)
+	}
+}
+
+impl<S: BlockingSource> Iterator for FrameIter<S> {
+	type Item = io::Result<Window>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done { return None; }
+		loop {
+			match self.frame.fill_blocking(&mut self.source) {
+				Ok(outcome) if !outcome.eof => if let Some(window) = self.frame.window() { return Some(Ok(window)); },
+				Ok(_) => {
+					self.done = true;
+					return self.frame.last_window().map(Ok);
+				}
+				Err(err) => {
+					self.done = true;
+					return Some(Err(err));
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::FrameIter;
+
+	#[test]
+	fn test_iter() {
+		let mut iter = FrameIter::new(&b"Hello world!"[..], 8, 2);
+		let ptr = iter.frame.buf.as_ptr() as usize;
+		let mut windows = Vec::new();
+		for window in &mut iter {
+			let window = window.unwrap();
+			windows.push((window.offset, window.consumed.to_vec(), window.preserved.to_vec()));
+		}
+		assert_eq!(windows, vec![
+			(0, b"Hello ".to_vec(), b"wo".to_vec()),
+			(6, b"worl".to_vec(), b"d!".to_vec()),
+			(10, b"d!".to_vec(), b"".to_vec()),
+		]);
+		// check that no reallocation caused, buffer still point into original allocation
+		assert!((ptr..ptr + 8).contains(&(iter.frame.buf.as_ptr() as usize)));
+	}
+
+	#[test]
+	fn test_iter_min_fill() {
+		// single byte per read is less than preserved region
+		let frame = crate::FrameBuilder::new(16, 4).min_fill(1).build().unwrap();
+		let mut data = Vec::new();
+		for window in FrameIter::with_frame(frame, crate::tests::Trickle(b"Hello world!", 1)) {
+			data.extend_from_slice(&window.unwrap().consumed);
+		}
+		assert_eq!(data, b"Hello world!");
+	}
+}
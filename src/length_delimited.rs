@@ -0,0 +1,187 @@
+//! Decode length prefixed frames on top of [`Frame`]
+
+use std::io;
+
+use bytes::BytesMut;
+
+#[cfg(feature = "read_std")]
+use crate::BlockingSource;
+use crate::{Frame, Source};
+
+/// Width and byte order of length field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthField {
+	U16Be,
+	U16Le,
+	U32Be,
+	U32Le,
+}
+
+impl LengthField {
+	fn len(self) -> usize {
+		match self {
+			LengthField::U16Be | LengthField::U16Le => 2,
+			LengthField::U32Be | LengthField::U32Le => 4,
+		}
+	}
+
+	fn read(self, src: &[u8]) -> u64 {
+		match self {
+			LengthField::U16Be => u16::from_be_bytes([src[0], src[1]]) as u64,
+			LengthField::U16Le => u16::from_le_bytes([src[0], src[1]]) as u64,
+			LengthField::U32Be => u32::from_be_bytes([src[0], src[1], src[2], src[3]]) as u64,
+			LengthField::U32Le => u32::from_le_bytes([src[0], src[1], src[2], src[3]]) as u64,
+		}
+	}
+}
+
+/// Split stream into payloads prefixed with their length
+///
+/// Every frame is `header_offset` bytes of header, length field and payload, header and length field are stripped.
+/// Payload which fit into window is split out of frame buffer without copying, larger one is collected into its own buffer.
+pub struct LengthDelimited {
+	frame: Frame,
+	field: LengthField,
+	header_offset: usize,
+	length_adjustment: i64,
+	max_frame_len: usize,
+	// payload larger than window and number of bytes still missing
+	partial: Option<(BytesMut, usize)>,
+	eof: bool,
+}
+
+impl LengthDelimited {
+	pub fn new(capacity: usize, field: LengthField, max_frame_len: usize) -> Self {
+		Self {
+			frame: Frame::raw(capacity, 0),
+			field,
+			header_offset: 0,
+			length_adjustment: 0,
+			max_frame_len,
+			partial: None,
+			eof: false,
+		}
+	}
+
+	/// Number of header bytes before length field
+	pub fn header_offset(mut self, header_offset: usize) -> Self {
+		self.header_offset = header_offset;
+		self
+	}
+
+	/// Value added to length field to get payload length, e.g. `-2` when u16 length count itself
+	pub fn length_adjustment(mut self, length_adjustment: i64) -> Self {
+		self.length_adjustment = length_adjustment;
+		self
+	}
+
+	/// Next payload from buffered data, `None` when frame has to be refilled
+	fn decode(&mut self) -> Option<io::Result<Option<BytesMut>>> {
+		if let Some((mut payload, missing)) = self.partial.take() {
+			let (_, chunk) = self.frame.split_to(missing.min(self.frame.len()));
+			payload.extend_from_slice(&chunk);
+			let missing = missing - chunk.len();
+			if missing == 0 { return Some(Ok(Some(payload))); }
+			self.partial = Some((payload, missing));
+			return if self.eof { Some(Err(io::ErrorKind::UnexpectedEof.into())) } else { None };
+		}
+		let head = self.header_offset + self.field.len();
+		let len = self.frame.len();
+		if len < head {
+			return match (self.eof, len) {
+				(false, _) => None,
+				(true, 0) => Some(Ok(None)),
+				(true, _) => Some(Err(io::ErrorKind::UnexpectedEof.into())),
+			};
+		}
+		let field = self.field.read(&self.frame[self.header_offset..head]);
+		let payload = match (field as i64).checked_add(self.length_adjustment) {
+			Some(payload) if payload < 0 => return Some(Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+				"length field {} adjusted by {} is negative", field, self.length_adjustment
+			)))),
+			Some(payload) if payload as u64 <= self.max_frame_len as u64 => payload as usize,
+			_ => return Some(Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+				"length field {} adjusted by {} exceeds limit of {} bytes", field, self.length_adjustment, self.max_frame_len
+			)))),
+		};
+		if len >= head + payload {
+			self.frame.split_to(head);
+			Some(Ok(Some(self.frame.split_to(payload).1)))
+		} else if head + payload > self.frame.capacity {
+			self.frame.split_to(head);
+			self.partial = Some((BytesMut::with_capacity(payload), payload));
+			self.decode()
+		} else if self.eof {
+			Some(Err(io::ErrorKind::UnexpectedEof.into()))
+		} else {
+			None
+		}
+	}
+
+	/// Next payload, `None` once source is exhausted at frame boundary
+	pub async fn next<S: Source + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<BytesMut>> {
+		loop {
+			if let Some(payload) = self.decode() { return payload; }
+			self.eof = self.frame.fill(source).await?.eof;
+		}
+	}
+
+	/// Blocking version of [`LengthDelimited::next`]
+	#[cfg(feature = "read_std")]
+	pub fn next_blocking<S: BlockingSource + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<BytesMut>> {
+		loop {
+			if let Some(payload) = self.decode() { return payload; }
+			self.eof = self.frame.fill_blocking(source)?.eof;
+		}
+	}
+}
+
+#[cfg(all(test, feature = "read_std"))]
+mod tests {
+	use super::{LengthDelimited, LengthField};
+
+	fn decode(mut decoder: LengthDelimited, mut source: &[u8]) -> Vec<Result<Vec<u8>, std::io::ErrorKind>> {
+		let mut result = Vec::new();
+		loop {
+			match decoder.next_blocking(&mut source) {
+				Ok(Some(payload)) => result.push(Ok(payload.to_vec())),
+				Ok(None) => break,
+				Err(err) => {
+					result.push(Err(err.kind()));
+					break;
+				}
+			}
+		}
+		result
+	}
+
+	#[test]
+	fn test_length_delimited() {
+		let source = b"\x00\x03abc\x00\x00\x00\x0blonger than";
+		assert_eq!(decode(LengthDelimited::new(8, LengthField::U16Be, 64), source), vec![
+			Ok(b"abc".to_vec()),
+			Ok(b"".to_vec()),
+			Ok(b"longer than".to_vec()),
+		]);
+		let source = b"\x01\x05\x00hey\x02\x04\x00hi";
+		let decoder = LengthDelimited::new(8, LengthField::U16Le, 64).header_offset(1).length_adjustment(-2);
+		assert_eq!(decode(decoder, source), vec![Ok(b"hey".to_vec()), Ok(b"hi".to_vec())]);
+	}
+
+	#[test]
+	fn test_length_delimited_errors() {
+		let source = b"\x00\x00\x00\x03abc\x00\x00\x01\x00";
+		assert_eq!(decode(LengthDelimited::new(8, LengthField::U32Be, 16), source), vec![
+			Ok(b"abc".to_vec()),
+			Err(std::io::ErrorKind::InvalidData),
+		]);
+		assert_eq!(decode(LengthDelimited::new(8, LengthField::U16Be, 16), b"\x00\x05ab"), vec![
+			Err(std::io::ErrorKind::UnexpectedEof),
+		]);
+		// negative and overflowing adjusted length
+		let decoder = LengthDelimited::new(8, LengthField::U16Be, 16).length_adjustment(-2);
+		assert_eq!(decode(decoder, b"\x00\x01a"), vec![Err(std::io::ErrorKind::InvalidData)]);
+		let decoder = LengthDelimited::new(8, LengthField::U32Be, 16).length_adjustment(i64::MAX);
+		assert_eq!(decode(decoder, b"\xff\xff\xff\xff"), vec![Err(std::io::ErrorKind::InvalidData)]);
+	}
+}
@@ -1,43 +1,198 @@
 extern crate core;
 
+use std::future::Future;
 use std::ops::Deref;
+use std::task::{Context, Poll};
 
-use bytes::BytesMut;
+use bytes::{Buf, BytesMut};
 #[cfg(feature = "monoio")]
 use monoio::io::AsyncReadRent;
 #[cfg(feature = "tokio")]
-use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::io::AsyncRead;
+
+pub use builder::FrameBuilder;
+#[cfg(feature = "read_std")]
+pub use source::BlockingSource;
+pub use source::{SendSource, Source};
+use source::PollSource;
+
+mod builder;
+#[cfg(feature = "tokio-util")]
+pub mod codec;
+#[cfg(all(feature = "direct", target_os = "linux"))]
+pub mod direct;
+#[cfg(feature = "read_std")]
+pub mod iter;
+pub mod length_delimited;
+pub mod lines;
+#[cfg(all(feature = "mmap", unix))]
+pub mod mmap;
+#[cfg(feature = "aho-corasick")]
+pub mod multi;
+#[cfg(feature = "regex")]
+pub mod regex;
+#[cfg(all(feature = "ring", target_os = "linux"))]
+pub mod ring;
+#[cfg(any(feature = "memchr", feature = "aho-corasick", feature = "regex"))]
+mod scan;
+#[cfg(feature = "memchr")]
+pub mod search;
+#[cfg(feature = "shard")]
+pub mod shard;
+pub mod source;
+#[cfg(feature = "futures-core")]
+pub mod stream;
+
+/// Part of stream handed out by single [`Frame::consume`]
+#[derive(Debug)]
+pub struct Window {
+	/// Absolute offset of first consumed byte
+	pub offset: u64,
+	/// Bytes which are not part of any other window's `consumed`
+	pub consumed: BytesMut,
+	/// Copy of preserved region which follow consumed bytes, it's consumed by next window
+	pub preserved: BytesMut,
+}
+
+impl Window {
+	/// Absolute offset of preserved region
+	pub fn preserved_offset(&self) -> u64 {
+		self.offset + self.consumed.len() as u64
+	}
+}
+
+/// Result of single [`Frame::fill`] call
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReadOutcome {
+	/// Bytes read by this call, may be non zero even when `eof` is set
+	pub read: usize,
+	/// Buffer has no spare capacity left
+	pub full: bool,
+	/// Source reached end of stream or limit set by [`Frame::take`] was reached
+	pub eof: bool,
+	/// Last read returned less than spare capacity it was given
+	pub short: bool,
+}
+
+/// Error of fallible [`Frame`] operations
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameError {
+	/// Preserved region is smaller than quarter of capacity
+	PreservedTooSmall { capacity: usize, preserved: usize },
+	/// Preserved region doesn't leave any room for new data
+	PreservedTooLarge { capacity: usize, preserved: usize },
+	/// Buffer hold less than preserved region, there is nothing to consume
+	NotEnoughData { len: usize, preserved: usize },
+	/// Slice doesn't fit into spare capacity, nothing was written
+	Overflow { len: usize, available: usize },
+	/// Frame already grown to its maximum capacity
+	MaxCapacity { max_capacity: usize },
+	/// Alignment is not power of two
+	Alignment { align: usize },
+}
+
+impl std::fmt::Display for FrameError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			FrameError::PreservedTooSmall { capacity, preserved } => write!(f, "preserved size {} is less than quarter of capacity {}", preserved, capacity),
+			FrameError::PreservedTooLarge { capacity, preserved } => write!(f, "preserved size {} must be less than capacity {}", preserved, capacity),
+			FrameError::NotEnoughData { len, preserved } => write!(f, "buffer hold {} bytes which is less than preserved size {}", len, preserved),
+			FrameError::Overflow { len, available } => write!(f, "slice of {} bytes doesn't fit into {} bytes of spare capacity", len, available),
+			FrameError::MaxCapacity { max_capacity } => write!(f, "token doesn't fit into maximum capacity of {} bytes", max_capacity),
+			FrameError::Alignment { align } => write!(f, "alignment {} is not power of two", align),
+		}
+	}
+}
+
+impl std::error::Error for FrameError {}
+
+impl From<FrameError> for std::io::Error {
+	fn from(err: FrameError) -> Self {
+		std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+	}
+}
 
 /// Buffer frame allow to read new data and retain some part of buffer
 pub struct Frame {
 	preserved: usize,
 	capacity: usize,
 	written: u64,
+	offset: u64,
+	limit: u64,
+	// bytes at start of buffer which were already part of previous window
+	overlap: usize,
+	// fill keep reading until this many bytes were read or buffer is full, `None` follow preserved size
+	min_fill: Option<usize>,
+	// maximum number of reads issued by single fill
+	max_reads: usize,
+	// capacity frame return to once incomplete token is consumed
+	base_capacity: usize,
+	max_capacity: usize,
+	// caller marked buffered data as incomplete token, capacity may be above base
+	incomplete: bool,
+	// address next read start at is kept multiple of this
+	align: usize,
+	// largest ratio of capacity to preserved size, `0` when it isn't checked
+	max_ratio: usize,
 	buf: BytesMut,
-	#[cfg(feature = "monoio")]
-	spare_buf: Option<BytesMut>,
-	#[cfg(feature = "monoio")]
-	_marker: std::marker::PhantomPinned,
 }
 
 impl Frame {
 	pub fn new(capacity: usize, preserved: usize) -> Self {
 		if preserved < (capacity >> 2) { panic!("Please use larger buffer size") }
+		Self { max_ratio: 4, ..Self::raw(capacity, preserved) }
+	}
+
+	/// Fallible version of [`Frame::new`], also reject `preserved` which leave no room for new data
+	pub fn try_new(capacity: usize, preserved: usize) -> Result<Self, FrameError> {
+		FrameBuilder::new(capacity, preserved).build()
+	}
+
+	/// Create frame without checking ratio of `preserved` to `capacity`
+	pub(crate) fn raw(capacity: usize, preserved: usize) -> Self {
 		Self {
 			buf: BytesMut::with_capacity(capacity),
 			capacity,
 			preserved,
-			#[cfg(feature = "monoio")]
-			spare_buf: Some(BytesMut::with_capacity(0)),
-			#[cfg(feature = "monoio")]
-			_marker: std::marker::PhantomPinned,
 			written: 0,
+			offset: 0,
+			limit: u64::MAX,
+			overlap: 0,
+			min_fill: None,
+			max_reads: usize::MAX,
+			base_capacity: capacity,
+			max_capacity: capacity,
+			incomplete: false,
+			align: 1,
+			max_ratio: 0,
 		}
 	}
 
 	#[inline]
 	fn reserve(&mut self) {
-		self.buf.reserve(self.capacity - self.preserved)
+		// buffer which isn't full is only topped up to capacity, so window never exceed capacity even when frame
+		// retain more or less than preserved region (consume_delimited, line reader), full buffer still grow
+		let len = self.buf.len();
+		let additional = if len < self.capacity { self.capacity - len } else { self.capacity - self.preserved };
+		// reclaiming consumed space move retained data to start of allocation, which isn't aligned
+		if self.align > 1 && self.buf.capacity() - len < additional {
+			self.buf.reserve(additional + self.align - 1);
+			self.align_tail();
+			// padding may leave more room than asked, keep window within capacity
+			drop(self.buf.split_off(len + additional));
+		} else {
+			self.buf.reserve(additional);
+		}
+	}
+
+	/// Shift buffered data into spare capacity, so next read start at aligned address
+	fn align_tail(&mut self) {
+		let len = self.buf.len();
+		let pad = (self.buf.as_ptr() as usize + len).wrapping_neg() & (self.align - 1);
+		if pad == 0 { return; }
+		self.buf.resize(len + pad, 0);
+		self.buf.copy_within(..len, pad);
+		self.buf.advance(pad);
 	}
 
 	/// Push slice into buffer
@@ -49,89 +204,270 @@ impl Frame {
 		need
 	}
 
-	#[cfg(feature = "tokio")]
-	pub async fn read_tokio<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> std::io::Result<bool> {
+	/// Push whole slice into buffer or nothing when it doesn't fit
+	pub fn extend_all(&mut self, slice: &[u8]) -> Result<(), FrameError> {
 		self.reserve();
-		loop {
-			match reader.read_buf(&mut self.buf).await {
-				Ok(0) => {
-					break Ok(false);
-				}
-				Ok(n) => {
-					self.written += n as u64;
-					if n < (self.preserved << 1) {
-						continue;
-					} else {
-						break Ok(true);
-					}
-				}
-				Err(err) => {
-					break Err(err);
-				}
+		let available = self.buf.capacity() - self.buf.len();
+		if slice.len() > available { return Err(FrameError::Overflow { len: slice.len(), available }); }
+		self.extend_from_slice(slice);
+		Ok(())
+	}
+
+	/// Change size of preserved region, buffered data is kept as is and new size apply from next [`Frame::consume`]
+	///
+	/// Bytes already handed out as preserved region stay at start of buffer, so larger size only make next window shorter
+	/// and smaller one make it longer. Minimum fill set by [`FrameBuilder::min_fill`] is not changed,
+	/// default one follow new size. Ratio of capacity to preserved size is checked same way as when frame was created.
+	pub fn set_preserved(&mut self, preserved: usize) -> Result<(), FrameError> {
+		let capacity = self.base_capacity;
+		if preserved >= capacity { return Err(FrameError::PreservedTooLarge { capacity, preserved }); }
+		if self.max_ratio > 0 && preserved < capacity / self.max_ratio { return Err(FrameError::PreservedTooSmall { capacity, preserved }); }
+		self.preserved = preserved;
+		Ok(())
+	}
+
+	/// Mark buffered data as incomplete token which need more room, double capacity up to maximum set by
+	/// [`FrameBuilder::max_capacity`] and return new capacity
+	///
+	/// Capacity shrink back to base once anything is consumed
+	pub fn mark_incomplete(&mut self) -> Result<usize, FrameError> {
+		if self.capacity >= self.max_capacity { return Err(FrameError::MaxCapacity { max_capacity: self.max_capacity }); }
+		self.capacity = self.capacity.saturating_mul(2).min(self.max_capacity);
+		self.incomplete = true;
+		Ok(self.capacity)
+	}
+
+	/// Whether frame is grown for incomplete token
+	pub fn is_incomplete(&self) -> bool {
+		self.incomplete
+	}
+
+	/// Let sources read only `limit` more bytes, once reached [`Frame::fill`] report end of stream
+	pub fn take(&mut self, limit: u64) {
+		self.limit = self.written.saturating_add(limit);
+	}
+
+	/// Detach spare capacity past limit so source can't read beyond it, `None` once limit is reached
+	fn split_limit(&mut self) -> Option<BytesMut> {
+		// slice pushed by extend_from_slice may already cross the limit
+		let left = self.limit.saturating_sub(self.written);
+		if left == 0 { return None; }
+		if left < (self.buf.capacity() - self.buf.len()) as u64 {
+			Some(self.buf.split_off(self.buf.len() + left as usize))
+		} else {
+			Some(BytesMut::new())
+		}
+	}
+
+	/// Account result of single read which had `spare` bytes of room, return `Some` once fill loop should stop
+	fn filled(&mut self, outcome: &mut ReadOutcome, reads: &mut usize, spare: usize, res: std::io::Result<usize>) -> Option<std::io::Result<ReadOutcome>> {
+		match res {
+			Ok(n) => {
+				self.written += n as u64;
+				*reads += 1;
+				outcome.read += n;
+				outcome.short = n < spare;
+				outcome.eof = n == 0;
+				// buffer is full, nothing more can be read without dropping data
+				let room = n != 0 && self.buf.len() < self.buf.capacity();
+				// whatever fill policy is, window has to hold more than preserved region so it can be consumed
+				let wanted = self.buf.len() <= self.preserved || (outcome.read < self.min_fill.unwrap_or(self.preserved << 1) && *reads < self.max_reads);
+				if room && wanted { return None; }
+				outcome.full = self.buf.len() == self.buf.capacity();
+				Some(Ok(*outcome))
 			}
+			Err(err) if err.kind() == std::io::ErrorKind::Interrupted => None,
+			Err(err) => Some(Err(err)),
 		}
 	}
 
-	#[cfg(feature = "monoio")]
-	pub async fn read_monoio<R: AsyncReadRent + Unpin>(&mut self, reader: &mut R) -> std::io::Result<bool> {
+	/// Outcome of fill loop which stopped because limit set by [`Frame::take`] was reached
+	fn limited(&self, mut outcome: ReadOutcome) -> std::io::Result<ReadOutcome> {
+		outcome.eof = true;
+		outcome.full = self.buf.len() == self.buf.capacity();
+		Ok(outcome)
+	}
+
+	/// Read from `source` until enough data is buffered, see [`ReadOutcome`] for what happened during read
+	pub async fn fill<S: Source + ?Sized>(&mut self, source: &mut S) -> std::io::Result<ReadOutcome> {
 		self.reserve();
-		let mut spare = self.spare_buf.take().unwrap_or_default();
-		std::mem::swap(&mut spare, &mut self.buf);
+		let (mut outcome, mut reads) = (ReadOutcome::default(), 0);
 		loop {
-			let (res, buf) = reader.read(spare).await;
-			spare = buf;
-			std::mem::swap(&mut spare, &mut self.buf);
-			match res {
-				Ok(0) => { break Ok(false); }
-				Ok(n) => {
-					self.written += n as u64;
-					if n < (self.preserved << 1) {
-						continue;
-					} else {
-						break Ok(true);
-					}
-				}
-				Err(err) => break Err(err)
-			}
+			let Some(rest) = self.split_limit() else { break self.limited(outcome) };
+			let spare = self.buf.capacity() - self.buf.len();
+			let res = source.read_buf(&mut self.buf).await;
+			let _ = self.buf.try_unsplit(rest);
+			if let Some(res) = self.filled(&mut outcome, &mut reads, spare, res) { break res; }
 		}
 	}
 
-	#[cfg(feature = "read_monoio_file")]
-	pub async fn read_monoio_file(&mut self, reader: &monoio::fs::File) -> std::io::Result<bool> {
+	/// Blocking version of [`Frame::fill`]
+	#[cfg(feature = "read_std")]
+	pub fn fill_blocking<S: BlockingSource + ?Sized>(&mut self, source: &mut S) -> std::io::Result<ReadOutcome> {
 		self.reserve();
-		let mut spare = self.spare_buf.take().unwrap_or_default();
-		std::mem::swap(&mut spare, &mut self.buf);
+		let (mut outcome, mut reads) = (ReadOutcome::default(), 0);
 		loop {
-			let buf = spare.split_off(spare.len());
-			let (res, buf) = reader.read_at(buf, self.written).await;
-			spare.unsplit(buf);
-			std::mem::swap(&mut spare, &mut self.buf);
-			match res {
-				Ok(0) => { break Ok(false); }
-				Ok(n) => {
-					self.written += n as u64;
-					if n < (self.preserved << 1) {
-						continue;
-					} else {
-						break Ok(true);
-					}
-				}
-				Err(err) => break Err(err)
-			}
+			let Some(rest) = self.split_limit() else { break self.limited(outcome) };
+			let spare = self.buf.capacity() - self.buf.len();
+			let res = source.read_buf(&mut self.buf);
+			let _ = self.buf.try_unsplit(rest);
+			if let Some(res) = self.filled(&mut outcome, &mut reads, spare, res) { break res; }
+		}
+	}
+
+	#[cfg(feature = "read_std")]
+	pub fn read_std<R: std::io::Read>(&mut self, reader: &mut R) -> std::io::Result<ReadOutcome> {
+		self.fill_blocking(reader)
+	}
+
+	#[cfg(feature = "tokio")]
+	pub async fn read_tokio<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> std::io::Result<ReadOutcome> {
+		self.fill(&mut source::Tokio(reader)).await
+	}
+
+	#[cfg(feature = "futures-io")]
+	pub async fn read_futures<R: futures_io::AsyncRead + Unpin>(&mut self, reader: &mut R) -> std::io::Result<ReadOutcome> {
+		self.fill(&mut source::Futures(reader)).await
+	}
+
+	#[cfg(feature = "monoio")]
+	pub async fn read_monoio<R: AsyncReadRent>(&mut self, reader: &mut R) -> std::io::Result<ReadOutcome> {
+		self.fill(&mut source::Monoio(reader)).await
+	}
+
+	#[cfg(feature = "read_monoio_file")]
+	pub async fn read_monoio_file(&mut self, reader: &monoio::fs::File) -> std::io::Result<ReadOutcome> {
+		self.fill(&mut source::MonoioFile::new(reader, self.written)).await
+	}
+
+	/// Drive reads of fill in progress, `outcome` and `reads` keep its progress between polls
+	fn poll_fill<R: PollSource + ?Sized>(&mut self, cx: &mut Context<'_>, reader: &mut R, outcome: &mut ReadOutcome, reads: &mut usize) -> Poll<std::io::Result<ReadOutcome>> {
+		loop {
+			let Some(rest) = self.split_limit() else { return Poll::Ready(self.limited(*outcome)) };
+			let spare = self.buf.capacity() - self.buf.len();
+			let res = reader.poll_read_buf(cx, &mut self.buf);
+			let _ = self.buf.try_unsplit(rest);
+			let Poll::Ready(res) = res else { return Poll::Pending };
+			if let Some(res) = self.filled(outcome, reads, spare, res) { return Poll::Ready(res); }
 		}
 	}
 
-	/// Get current slice of data and advance buffer
-	pub fn consume(&mut self) -> BytesMut {
-		self.buf.split_to(self.buf.len() - self.preserved)
+	/// Like [`Frame::fill`], but return whatever was read so far once `deadline` complete while `reader` is waiting for data
+	///
+	/// Buffer may hold no more than preserved region then, so [`Frame::consume`] would panic.
+	/// Use [`Frame::try_consume`] or check that [`Frame::preserved_offset`] is past [`Frame::offset`] first.
+	pub async fn fill_until<R, D>(&mut self, reader: &mut R, deadline: D) -> std::io::Result<ReadOutcome>
+		where R: PollSource + ?Sized,
+		      D: Future {
+		self.reserve();
+		let (mut outcome, mut reads) = (ReadOutcome::default(), 0);
+		let mut deadline = std::pin::pin!(deadline);
+		std::future::poll_fn(|cx| match self.poll_fill(cx, reader, &mut outcome, &mut reads) {
+			Poll::Pending if deadline.as_mut().poll(cx).is_ready() => Poll::Ready(Ok(outcome)),
+			res => res,
+		}).await
+	}
+
+	/// Like [`Frame::fill`], but return as soon as `reader` would block once any data was read
+	///
+	/// Same as for [`Frame::fill_until`], buffer may hold no more than preserved region when it return.
+	pub async fn fill_ready<R: PollSource + ?Sized>(&mut self, reader: &mut R) -> std::io::Result<ReadOutcome> {
+		self.reserve();
+		let (mut outcome, mut reads) = (ReadOutcome::default(), 0);
+		std::future::poll_fn(|cx| match self.poll_fill(cx, reader, &mut outcome, &mut reads) {
+			Poll::Pending if outcome.read > 0 => Poll::Ready(Ok(outcome)),
+			res => res,
+		}).await
+	}
+
+	/// Absolute offset of first byte of current window in stream
+	pub fn offset(&self) -> u64 {
+		self.offset
+	}
+
+	/// Absolute offset of preserved region, which will be start of window after [`Frame::consume`]
+	pub fn preserved_offset(&self) -> u64 {
+		self.offset + self.buf.len().saturating_sub(self.preserved) as u64
+	}
+
+	/// Set absolute offset of current window, use when source doesn't start at beginning of stream
+	pub fn set_offset(&mut self, offset: u64) {
+		self.offset = offset;
+	}
+
+	/// Get current slice of data with its absolute offset and advance buffer
+	pub fn consume(&mut self) -> (u64, BytesMut) {
+		self.overlap = self.preserved;
+		self.split_to(self.buf.len() - self.preserved)
+	}
+
+	/// Fallible version of [`Frame::consume`], fail instead of panic when buffer hold less than preserved region
+	pub fn try_consume(&mut self) -> Result<(u64, BytesMut), FrameError> {
+		if self.buf.len() < self.preserved { return Err(FrameError::NotEnoughData { len: self.buf.len(), preserved: self.preserved }); }
+		Ok(self.consume())
+	}
+
+	/// Get current slice of data up to and including last `delimiter` with its absolute offset,
+	/// incomplete record after it is retained instead of preserved region
+	///
+	/// Return `None` and keep buffer untouched when there is no delimiter in buffer
+	pub fn consume_delimited(&mut self, delimiter: &[u8]) -> Option<(u64, BytesMut)> {
+		let end = match delimiter {
+			[] => return None,
+			[byte] => self.buf.iter().rposition(|b| b == byte)? + 1,
+			_ => self.buf.windows(delimiter.len()).rposition(|w| w == delimiter)? + delimiter.len(),
+		};
+		self.overlap = 0;
+		Some(self.split_to(end))
+	}
+
+	/// Consume current window, keeping copy of preserved region as its context,
+	/// `None` when there is nothing but preserved region
+	#[cfg(any(feature = "futures-core", feature = "read_std"))]
+	fn window(&mut self) -> Option<Window> {
+		if self.buf.len() <= self.preserved { return None; }
+		let preserved = BytesMut::from(&self.buf[self.buf.len() - self.preserved..]);
+		let (offset, consumed) = self.consume();
+		Some(Window { offset, consumed, preserved })
+	}
+
+	/// Hand out everything left once source reached end of stream, `None` when buffer is empty
+	#[cfg(any(feature = "futures-core", feature = "read_std"))]
+	fn last_window(&mut self) -> Option<Window> {
+		if self.buf.is_empty() { return None; }
+		let (offset, consumed) = self.split_to(self.buf.len());
+		self.overlap = 0;
+		Some(Window { offset, consumed, preserved: BytesMut::new() })
+	}
+
+	/// Hand out first `len` bytes with their absolute offset regardless of preserved region
+	pub(crate) fn split_to(&mut self, len: usize) -> (u64, BytesMut) {
+		let offset = self.offset;
+		self.offset += len as u64;
+		let split = (offset, self.buf.split_to(len));
+		if self.incomplete { self.shrink(); }
+		split
+	}
+
+	/// Return to base capacity after incomplete token was consumed, move what's left into buffer of base size
+	/// so grown allocation is released once consumed token is dropped
+	fn shrink(&mut self) {
+		self.incomplete = false;
+		self.capacity = self.base_capacity;
+		if self.buf.len() <= self.capacity {
+			let mut buf = BytesMut::with_capacity(self.capacity + self.align - 1);
+			buf.extend_from_slice(&self.buf);
+			self.buf = buf;
+			self.align_tail();
+		}
 	}
 
 	/// Get all buffer without preserving
 	pub fn finish(self) -> BytesMut {
-		// if written more than existing mean already preserve data at start
-		if self.written > self.buf.len() as u64 {
+		// if consume retained preserved data at start, it was already part of previous window
+		if self.overlap > 0 {
 			let mut buf = self.buf;
-			let _ = buf.split_to(self.preserved);
+			let _ = buf.split_to(self.overlap);
 			buf
 		} else {
 			// single buffer
@@ -146,11 +482,73 @@ impl Deref for Frame {
 	fn deref(&self) -> &Self::Target { &self.buf }
 }
 
+/// Window sliding over stream with preserved region, common part of [`Frame`] and frames which don't own their data
+///
+/// Fill isn't part of it since [`Frame`] read from source, see [`Mapped`] for frames which fill without one.
+pub trait Windowed: Deref<Target = [u8]> {
+	/// Consumed part of window, owned or borrowed from frame
+	type Consumed<'a>: Deref<Target = [u8]> where Self: 'a;
+
+	/// Absolute offset of first byte of current window in stream
+	fn offset(&self) -> u64;
+
+	/// Size of preserved region
+	fn preserved(&self) -> usize;
+
+	/// Absolute offset of preserved region, which will be start of window after [`Windowed::consume`]
+	fn preserved_offset(&self) -> u64;
+
+	/// Get current slice of data except preserved region with its absolute offset and advance window past it
+	fn consume(&mut self) -> (u64, Self::Consumed<'_>);
+}
+
+/// [`Windowed`] over data which is already in memory, fill only expose more of it and never block
+pub trait Mapped: Windowed {
+	/// Extend window up to capacity, see [`Frame::fill`]
+	fn fill(&mut self) -> ReadOutcome;
+}
+
+impl Windowed for Frame {
+	type Consumed<'a> = BytesMut;
+
+	fn offset(&self) -> u64 { self.offset }
+
+	fn preserved(&self) -> usize { self.preserved }
+
+	fn preserved_offset(&self) -> u64 { Frame::preserved_offset(self) }
+
+	fn consume(&mut self) -> (u64, BytesMut) { Frame::consume(self) }
+}
+
 #[cfg(test)]
 mod tests {
 	use std::ops::Deref;
 
-	use crate::Frame;
+	use bytes::BytesMut;
+
+	use crate::{Frame, FrameBuilder, FrameError};
+	#[cfg(feature = "read_std")]
+	use crate::ReadOutcome;
+
+	/// Path in temp dir unique to test run, concurrent runs don't share files
+	#[cfg(any(feature = "shard", feature = "mmap", feature = "direct"))]
+	pub(crate) fn temp_path(name: &str) -> std::path::PathBuf {
+		std::env::temp_dir().join(format!("framed-stream-{}-{}", name, std::process::id()))
+	}
+
+	/// Reader which hand out at most chunk of bytes per read like slow socket
+	#[cfg(feature = "read_std")]
+	pub(crate) struct Trickle(pub(crate) &'static [u8], pub(crate) usize);
+
+	#[cfg(feature = "read_std")]
+	impl std::io::Read for Trickle {
+		fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+			let n = buf.len().min(self.0.len()).min(self.1);
+			buf[..n].copy_from_slice(&self.0[..n]);
+			self.0 = &self.0[n..];
+			Ok(n)
+		}
+	}
 
 	#[test]
 	fn test_bytes() {
@@ -158,8 +556,10 @@ mod tests {
 		let ptr = bytes.buf.as_ptr() as usize;
 		assert_eq!(bytes.extend_from_slice(b"Hello"), 5);
 		assert_eq!(bytes.deref(), b"Hello");
-		assert_eq!(&bytes.consume()[..], b"Hel");
+		assert_eq!(bytes.consume(), (0, BytesMut::from(&b"Hel"[..])));
+		assert_eq!(bytes.offset(), 3);
 		bytes.extend_from_slice(b"west");
+		assert_eq!(bytes.preserved_offset(), 7);
 		let ptr2 = bytes.buf.as_ptr() as usize;
 		assert_eq!(bytes.deref(), b"lowest");
 		// check that no reallocation caused
@@ -167,6 +567,125 @@ mod tests {
 		assert_eq!(bytes.finish().as_ref(), b"west");
 	}
 
+	#[test]
+	fn test_reserve() {
+		let mut bytes = Frame::new(8, 2);
+		assert_eq!(bytes.extend_from_slice(b"Hello"), 5);
+		// partial window is topped up to capacity, not grown past it
+		assert_eq!(bytes.extend_from_slice(b" world"), 3);
+		assert_eq!(bytes.buf.capacity(), 8);
+		assert_eq!(bytes.consume_delimited(b" "), Some((0, BytesMut::from(&b"Hello "[..]))));
+		assert_eq!(bytes.extend_from_slice(b"rld!!!!!"), 6);
+		assert_eq!(bytes.deref(), b"world!!!");
+		// full window which wasn't consumed still grow
+		assert_eq!(bytes.extend_from_slice(b"?"), 1);
+		assert_eq!(bytes.deref(), b"world!!!?");
+	}
+
+	#[test]
+	fn test_fallible() {
+		assert_eq!(Frame::try_new(8, 1).err(), Some(FrameError::PreservedTooSmall { capacity: 8, preserved: 1 }));
+		assert_eq!(Frame::try_new(8, 8).err(), Some(FrameError::PreservedTooLarge { capacity: 8, preserved: 8 }));
+		let mut bytes = Frame::try_new(8, 2).unwrap();
+		let ptr = bytes.buf.as_ptr() as usize;
+		assert_eq!(bytes.try_consume(), Err(FrameError::NotEnoughData { len: 0, preserved: 2 }));
+		assert_eq!(bytes.extend_all(b"Hello"), Ok(()));
+		assert_eq!(bytes.extend_all(b"world"), Err(FrameError::Overflow { len: 5, available: 3 }));
+		assert_eq!(bytes.deref(), b"Hello");
+		assert_eq!(bytes.try_consume(), Ok((0, BytesMut::from(&b"Hel"[..]))));
+		assert_eq!(bytes.extend_all(b"world"), Ok(()));
+		assert_eq!(bytes.deref(), b"loworld");
+		// check that no reallocation caused
+		assert_eq!(ptr, bytes.buf.as_ptr() as usize);
+	}
+
+	#[test]
+	fn test_incomplete() {
+		let mut bytes = FrameBuilder::new(8, 2).max_capacity(16).build().unwrap();
+		assert_eq!(bytes.extend_from_slice(b"abcdefghij"), 8);
+		assert_eq!(bytes.mark_incomplete(), Ok(16));
+		assert_eq!(bytes.extend_from_slice(b"ijklmnopq"), 8);
+		assert_eq!(bytes.mark_incomplete(), Err(FrameError::MaxCapacity { max_capacity: 16 }));
+		assert!(bytes.is_incomplete());
+		assert_eq!(bytes.consume(), (0, BytesMut::from(&b"abcdefghijklmn"[..])));
+		assert!(!bytes.is_incomplete());
+		assert_eq!(bytes.deref(), b"op");
+		assert_eq!(bytes.buf.capacity(), 8);
+	}
+
+	#[test]
+	fn test_set_preserved() {
+		let mut bytes = Frame::new(8, 2);
+		bytes.extend_from_slice(b"Hello wo");
+		assert_eq!(bytes.set_preserved(8), Err(FrameError::PreservedTooLarge { capacity: 8, preserved: 8 }));
+		assert_eq!(bytes.set_preserved(4), Ok(()));
+		assert_eq!(bytes.preserved_offset(), 4);
+		assert_eq!(bytes.consume(), (0, BytesMut::from(&b"Hell"[..])));
+		// same ratio as for Frame::new apply
+		assert_eq!(bytes.set_preserved(1), Err(FrameError::PreservedTooSmall { capacity: 8, preserved: 1 }));
+		assert_eq!(bytes.set_preserved(2), Ok(()));
+		assert_eq!(bytes.consume(), (4, BytesMut::from(&b"o "[..])));
+		assert_eq!(bytes.deref(), b"wo");
+		assert_eq!(bytes.set_preserved(3), Ok(()));
+		assert_eq!(bytes.try_consume(), Err(FrameError::NotEnoughData { len: 2, preserved: 3 }));
+		bytes.extend_from_slice(b"rld!");
+		assert_eq!(bytes.consume(), (6, BytesMut::from(&b"wor"[..])));
+		assert_eq!(bytes.deref(), b"ld!");
+		// frame built without ratio check accept any size
+		let mut bytes = FrameBuilder::new(8, 2).max_ratio(0).build().unwrap();
+		assert_eq!(bytes.set_preserved(0), Ok(()));
+	}
+
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_set_preserved_fill() {
+		let mut source = Trickle(b"Hello world, hello stream!", 2);
+		let mut bytes = Frame::new(16, 4);
+		assert_eq!(bytes.set_preserved(12), Ok(()));
+		// default minimum fill follow preserved size, so fill doesn't stop short of preserved region
+		assert_eq!(bytes.read_std(&mut source).unwrap().read, 16);
+		assert_eq!(bytes.consume(), (0, BytesMut::from(&b"Hell"[..])));
+		assert_eq!(bytes.set_preserved(4), Ok(()));
+		assert!(bytes.read_std(&mut source).unwrap().read >= 4);
+		let (offset, window) = bytes.consume();
+		assert_eq!(offset, 4);
+		assert!(window.starts_with(b"o world, hel"));
+	}
+
+	#[test]
+	fn test_consume_delimited() {
+		let mut bytes = Frame::new(8, 2);
+		let ptr = bytes.buf.as_ptr() as usize;
+		bytes.extend_from_slice(b"ab,cd,ef");
+		assert_eq!(bytes.consume_delimited(b","), Some((0, BytesMut::from(&b"ab,cd,"[..]))));
+		assert_eq!(bytes.offset(), 6);
+		bytes.extend_from_slice(b"gh::ij");
+		assert_eq!(bytes.deref(), b"efgh::ij");
+		// check that no reallocation caused
+		assert_eq!(ptr, bytes.buf.as_ptr() as usize);
+		assert_eq!(bytes.consume_delimited(b"::"), Some((6, BytesMut::from(&b"efgh::"[..]))));
+		assert_eq!(bytes.consume_delimited(b","), None);
+		assert_eq!(bytes.finish().as_ref(), b"ij");
+	}
+
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_bytes_std() {
+		let mut bytes = Frame::new(8, 2);
+		let mut reader = &b"Hello world!"[..];
+		let ptr = bytes.buf.as_ptr() as usize;
+		assert_eq!(bytes.read_std(&mut reader).unwrap(), ReadOutcome { read: 8, full: true, eof: false, short: false });
+		assert_eq!(bytes.deref(), b"Hello wo");
+		bytes.consume();
+		assert_eq!(bytes.read_std(&mut reader).unwrap(), ReadOutcome { read: 4, full: false, eof: false, short: true });
+		let ptr2 = bytes.buf.as_ptr() as usize;
+		assert_eq!(bytes.deref(), b"world!");
+		// check that no reallocation caused
+		assert_eq!(ptr, ptr2);
+		assert_eq!(bytes.read_std(&mut reader).unwrap(), ReadOutcome { read: 0, full: false, eof: true, short: true });
+		assert_eq!(bytes.finish().as_ref(), b"rld!");
+	}
+
 	#[cfg(feature = "tokio")]
 	#[tokio::test]
 	async fn test_bytes_tokio() {
@@ -188,6 +707,206 @@ mod tests {
 		assert_eq!(ptr, ptr2);
 	}
 
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_take() {
+		let mut bytes = Frame::new(8, 2);
+		let mut reader = &b"Hello world!"[..];
+		let ptr = bytes.buf.as_ptr() as usize;
+		bytes.take(9);
+		assert!(!bytes.read_std(&mut reader).unwrap().eof);
+		assert_eq!(bytes.deref(), b"Hello wo");
+		bytes.consume();
+		// last byte before limit is read in same call which report end of stream
+		assert_eq!(bytes.read_std(&mut reader).unwrap(), ReadOutcome { read: 1, full: false, eof: true, short: false });
+		assert_eq!(bytes.deref(), b"wor");
+		assert_eq!(reader, b"ld!");
+		// check that no reallocation caused
+		assert_eq!(ptr, bytes.buf.as_ptr() as usize);
+		assert_eq!(bytes.buf.capacity(), 8);
+
+		let mut bytes = Frame::new(8, 2);
+		bytes.take(2);
+		bytes.extend_from_slice(b"Hello");
+		assert_eq!(bytes.read_std(&mut reader).unwrap(), ReadOutcome { read: 0, full: false, eof: true, short: false });
+		assert_eq!(reader, b"ld!");
+	}
+
+	#[cfg(feature = "read_std")]
+	#[tokio::test]
+	async fn test_fill_generic() {
+		use crate::source::{Blocking, Source};
+		async fn first_window<S: Source>(mut source: S) -> (Vec<u8>, ReadOutcome) {
+			let mut bytes = Frame::new(8, 2);
+			let outcome = bytes.fill(&mut source).await.unwrap();
+			(bytes.to_vec(), outcome)
+		}
+		assert_eq!(first_window(Blocking(&b"Hello world!"[..])).await, (b"Hello wo".to_vec(), ReadOutcome { read: 8, full: true, eof: false, short: false }));
+		// short source is read and reach end of stream in single call
+		assert_eq!(first_window(Blocking(&b"Hey"[..])).await, (b"Hey".to_vec(), ReadOutcome { read: 3, full: false, eof: true, short: true }));
+	}
+
+	#[tokio::test]
+	async fn test_fill_deadline() {
+		use std::task::{Context, Poll};
+		use crate::source::PollSource;
+
+		// hand out chunks, then wait for more data forever like idle socket
+		struct Socket(Vec<&'static [u8]>);
+
+		impl PollSource for Socket {
+			fn poll_read_buf(&mut self, _: &mut Context<'_>, buf: &mut BytesMut) -> Poll<std::io::Result<usize>> {
+				if self.0.is_empty() { return Poll::Pending; }
+				let chunk = self.0.remove(0);
+				buf.extend_from_slice(chunk);
+				Poll::Ready(Ok(chunk.len()))
+			}
+		}
+
+		let mut bytes = Frame::new(8, 2);
+		let mut socket = Socket(vec![b"He", b"y"]);
+		let outcome = bytes.fill_ready(&mut socket).await.unwrap();
+		assert_eq!((outcome.read, outcome.eof), (3, false));
+		assert_eq!(bytes.deref(), b"Hey");
+		// nothing arrive before deadline
+		let outcome = bytes.fill_until(&mut socket, std::future::ready(())).await.unwrap();
+		assert_eq!((outcome.read, outcome.eof), (0, false));
+		let mut socket = Socket(vec![b"!", b"Hell"]);
+		let outcome = bytes.fill_until(&mut socket, std::future::pending::<()>()).await.unwrap();
+		assert_eq!((outcome.read, outcome.full), (5, true));
+		assert_eq!(bytes.deref(), b"Hey!Hell");
+		// single byte is less than preserved region, so there is nothing to consume yet
+		let mut bytes = Frame::new(8, 2);
+		assert_eq!(bytes.fill_ready(&mut Socket(vec![b"H"])).await.unwrap().read, 1);
+		assert_eq!(bytes.preserved_offset(), bytes.offset());
+		assert_eq!(bytes.try_consume(), Err(FrameError::NotEnoughData { len: 1, preserved: 2 }));
+	}
+
+	#[cfg(feature = "tokio")]
+	#[tokio::test]
+	async fn test_fill_ready_tokio() {
+		use tokio::io::AsyncWriteExt;
+
+		use crate::source::Tokio;
+
+		let (mut writer, reader) = tokio::io::duplex(64);
+		let mut reader = Tokio(reader);
+		let mut bytes = Frame::new(8, 2);
+		// pending read leave buffer as it was
+		let outcome = bytes.fill_until(&mut reader, std::future::ready(())).await.unwrap();
+		assert_eq!((outcome.read, bytes.len()), (0, 0));
+		writer.write_all(b"Hey").await.unwrap();
+		assert_eq!(bytes.fill_ready(&mut reader).await.unwrap().read, 3);
+		assert_eq!(bytes.deref(), b"Hey");
+		writer.write_all(b"Hello world!").await.unwrap();
+		drop(writer);
+		let outcome = bytes.fill_ready(&mut reader).await.unwrap();
+		assert_eq!((outcome.read, outcome.full), (5, true));
+		assert_eq!(bytes.deref(), b"HeyHello");
+	}
+
+	#[cfg(feature = "read_std")]
+	#[tokio::test]
+	async fn test_fill_send() {
+		use crate::source::{Blocking, SendSource, Sendable};
+		// generic fill over SendSource can be spawned
+		async fn first_window<S: SendSource + 'static>(source: S) -> Vec<u8> {
+			tokio::spawn(async move {
+				let mut bytes = Frame::new(8, 2);
+				bytes.fill(&mut Sendable(source)).await.unwrap();
+				bytes.to_vec()
+			}).await.unwrap()
+		}
+		assert_eq!(first_window(Blocking(&b"Hello world!"[..])).await, b"Hello wo");
+	}
+
+	#[cfg(feature = "tokio")]
+	#[tokio::test]
+	async fn test_fill_local() {
+		use std::pin::Pin;
+		use std::task::{Context, Poll};
+
+		use tokio::io::AsyncRead;
+
+		use crate::source::Tokio;
+
+		// reader which can't leave its thread, like one used on current-thread runtime
+		struct Local(&'static [u8], std::marker::PhantomData<std::rc::Rc<()>>);
+
+		impl AsyncRead for Local {
+			fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+				Pin::new(&mut self.0).poll_read(cx, buf)
+			}
+		}
+
+		let mut bytes = Frame::new(8, 2);
+		let mut source = Tokio(Local(b"Hello world!", std::marker::PhantomData));
+		assert_eq!(bytes.fill(&mut source).await.unwrap().read, 8);
+		assert_eq!(bytes.deref(), b"Hello wo");
+	}
+
+	#[cfg(feature = "futures-io")]
+	#[tokio::test]
+	async fn test_bytes_futures() {
+		let mut bytes = Frame::new(8, 2);
+		let mut reader = &b"Hello world!"[..];
+		let ptr = bytes.buf.as_ptr() as usize;
+		assert!(!bytes.read_futures(&mut reader).await.unwrap().eof);
+		assert_eq!(bytes.deref(), b"Hello wo");
+		bytes.consume();
+		assert!(!bytes.read_futures(&mut reader).await.unwrap().eof);
+		let ptr2 = bytes.buf.as_ptr() as usize;
+		assert_eq!(bytes.deref(), b"world!");
+		// check that no reallocation caused
+		assert_eq!(ptr, ptr2);
+		assert!(bytes.read_futures(&mut reader).await.unwrap().eof);
+		assert_eq!(bytes.finish().as_ref(), b"rld!");
+	}
+
+	#[test]
+	#[cfg(feature = "monoio")]
+	fn test_positional_monoio() {
+		use monoio::buf::IoBufMut;
+		use monoio::io::AsyncReadRentAt;
+		use monoio::FusionDriver;
+		use crate::source::Positional;
+
+		struct Memory(&'static [u8]);
+
+		impl AsyncReadRentAt for Memory {
+			type Future<'a, T> = std::future::Ready<monoio::BufResult<usize, T>> where Self: 'a, T: 'a;
+
+			fn read_at<T: IoBufMut>(&mut self, mut buf: T, pos: usize) -> Self::Future<'_, T> {
+				let src = &self.0[pos.min(self.0.len())..];
+				let n = src.len().min(buf.bytes_total());
+				unsafe {
+					std::ptr::copy_nonoverlapping(src.as_ptr(), buf.write_ptr(), n);
+					buf.set_init(n);
+				}
+				std::future::ready((Ok(n), buf))
+			}
+		}
+
+		monoio::RuntimeBuilder::<FusionDriver>::new()
+			.enable_all()
+			.build()
+			.unwrap()
+			.block_on(async {
+				let mut bytes = Frame::new(8, 2);
+				let mut source = Positional::new(Memory(b"Hello world!"), 6);
+				assert!(!bytes.fill(&mut source).await.unwrap().eof);
+				assert_eq!(bytes.deref(), b"world!");
+				assert_eq!(source.position(), 12);
+				assert!(bytes.fill(&mut source).await.unwrap().eof);
+
+				let mut bytes = Frame::new(8, 2);
+				let mut source = Positional::range(Memory(b"Hello world!"), 3..8);
+				assert_eq!(bytes.fill(&mut source).await.unwrap().read, 5);
+				assert!(bytes.fill(&mut source).await.unwrap().eof);
+				assert_eq!(bytes.deref(), b"lo wo");
+			});
+	}
+
 	#[test]
 	#[cfg(feature = "read_monoio_file")]
 	fn test_bytes_monoio() {
@@ -0,0 +1,241 @@
+//! Split stream into lines on top of [`Frame`]
+
+use std::io;
+
+use bytes::BytesMut;
+
+#[cfg(feature = "read_std")]
+use crate::BlockingSource;
+use crate::{Frame, Source};
+
+/// What to do with line which is longer than capacity, terminator isn't counted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LongLine {
+	/// Return [`io::ErrorKind::InvalidData`] error and skip rest of line
+	Error,
+	/// Return first `capacity` bytes of line and skip rest of it
+	Truncate,
+	/// Double frame capacity until whole line fit, line longer than `max_capacity` is handled like [`LongLine::Error`]
+	///
+	/// Frame shrink back to its capacity once long line is returned
+	Grow { max_capacity: usize },
+}
+
+/// Line without its `\n` or `\r\n` terminator
+#[derive(Debug)]
+pub struct Line {
+	/// Line number, starting from 1
+	pub number: u64,
+	/// Absolute offset of first byte of line
+	pub offset: u64,
+	pub bytes: BytesMut,
+}
+
+/// Read newline delimited stream line by line, lines are split out of frame buffer without copying
+pub struct Lines {
+	frame: Frame,
+	policy: LongLine,
+	// longest allowed line without terminator
+	max_len: usize,
+	number: u64,
+	// bytes at start of buffer which are known to contain no newline
+	scanned: usize,
+	// rest of too long line is discarded until next newline
+	skipping: bool,
+	eof: bool,
+}
+
+impl Lines {
+	pub fn new(capacity: usize, policy: LongLine) -> Self {
+		// room for `\r\n` on top of longest line, so line of exactly `capacity` bytes is found complete
+		let mut frame = Frame::raw(capacity + 2, 0);
+		let mut max_len = capacity;
+		if let LongLine::Grow { max_capacity } = policy {
+			max_len = max_capacity.max(capacity);
+			frame.max_capacity = max_len + 2;
+		}
+		Self {
+			frame,
+			policy,
+			max_len,
+			number: 0,
+			scanned: 0,
+			skipping: false,
+			eof: false,
+		}
+	}
+
+	fn take(&mut self, len: usize) -> Line {
+		let (offset, mut bytes) = self.frame.split_to(len);
+		self.scanned = 0;
+		self.number += 1;
+		if bytes.last() == Some(&b'\n') {
+			bytes.truncate(bytes.len() - 1);
+			if bytes.last() == Some(&b'\r') { bytes.truncate(bytes.len() - 1); }
+		}
+		Line { number: self.number, offset, bytes }
+	}
+
+	/// Apply policy to line which was taken whole, it may be still longer than allowed
+	fn checked(&self, mut line: Line) -> io::Result<Line> {
+		if line.bytes.len() <= self.max_len { return Ok(line); }
+		match self.policy {
+			LongLine::Truncate => {
+				line.bytes.truncate(self.max_len);
+				Ok(line)
+			}
+			LongLine::Error | LongLine::Grow { .. } => Err(self.too_long()),
+		}
+	}
+
+	fn too_long(&self) -> io::Error {
+		io::Error::new(io::ErrorKind::InvalidData, format!("line {} is longer than {} bytes", self.number, self.max_len))
+	}
+
+	/// Next line from buffered data, `None` when frame has to be refilled
+	fn line(&mut self) -> Option<io::Result<Option<Line>>> {
+		loop {
+			let found = self.frame[self.scanned..].iter().position(|&b| b == b'\n').map(|i| self.scanned + i);
+			let len = self.frame.len();
+			if self.skipping {
+				self.scanned = 0;
+				match found {
+					Some(i) => {
+						self.frame.split_to(i + 1);
+						self.skipping = false;
+						continue;
+					}
+					None => {
+						self.frame.split_to(len);
+						return if self.eof { Some(Ok(None)) } else { None };
+					}
+				}
+			}
+			return match found {
+				Some(i) => {
+					let line = self.take(i + 1);
+					Some(self.checked(line).map(Some))
+				}
+				None if self.eof => {
+					if len == 0 { return Some(Ok(None)); }
+					let line = self.take(len);
+					Some(self.checked(line).map(Some))
+				}
+				// full buffer without newline hold more than `max_len` bytes of line even if it end with `\r`
+				None if len >= self.frame.capacity => match self.policy {
+					LongLine::Truncate => {
+						let mut line = self.take(len);
+						line.bytes.truncate(self.max_len);
+						self.skipping = true;
+						Some(Ok(Some(line)))
+					}
+					LongLine::Grow { .. } if self.frame.mark_incomplete().is_ok() => {
+						self.scanned = len;
+						None
+					}
+					LongLine::Error | LongLine::Grow { .. } => {
+						self.number += 1;
+						self.skipping = true;
+						Some(Err(self.too_long()))
+					}
+				},
+				None => {
+					self.scanned = len;
+					None
+				}
+			};
+		}
+	}
+
+	/// Next line, `None` once source is exhausted
+	pub async fn next<S: Source + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<Line>> {
+		loop {
+			if let Some(line) = self.line() { return line; }
+			self.eof = self.frame.fill(source).await?.eof;
+		}
+	}
+
+	/// Blocking version of [`Lines::next`]
+	#[cfg(feature = "read_std")]
+	pub fn next_blocking<S: BlockingSource + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<Line>> {
+		loop {
+			if let Some(line) = self.line() { return line; }
+			self.eof = self.frame.fill_blocking(source)?.eof;
+		}
+	}
+}
+
+#[cfg(all(test, feature = "read_std"))]
+mod tests {
+	use super::{LongLine, Lines};
+
+	fn lines(policy: LongLine, mut source: &[u8]) -> Vec<Result<(u64, u64, String), std::io::ErrorKind>> {
+		// frame has room for `\r\n` on top of 8 bytes of line
+		let mut lines = Lines::new(8, policy);
+		let ptr = lines.frame.buf.as_ptr() as usize;
+		let mut result = Vec::new();
+		loop {
+			match lines.next_blocking(&mut source) {
+				Ok(Some(line)) => result.push(Ok((line.number, line.offset, String::from_utf8(line.bytes.to_vec()).unwrap()))),
+				Ok(None) => break,
+				Err(err) => result.push(Err(err.kind())),
+			}
+			// frame shrink back once long line is done with
+			if !lines.frame.is_incomplete() { assert_eq!(lines.frame.capacity, 10); }
+		}
+		// check that no reallocation caused, buffer still point into original allocation
+		if !matches!(policy, LongLine::Grow { .. }) { assert!((ptr..ptr + 10).contains(&(lines.frame.buf.as_ptr() as usize))); }
+		result
+	}
+
+	#[test]
+	fn test_lines() {
+		assert_eq!(lines(LongLine::Error, b"one\ntwo\r\nthree\n\nfour"), vec![
+			Ok((1, 0, "one".into())),
+			Ok((2, 4, "two".into())),
+			Ok((3, 9, "three".into())),
+			Ok((4, 15, "".into())),
+			Ok((5, 16, "four".into())),
+		]);
+	}
+
+	#[test]
+	fn test_long_lines() {
+		let source = b"short\nthis line is long\nend\n";
+		assert_eq!(lines(LongLine::Error, source), vec![
+			Ok((1, 0, "short".into())),
+			Err(std::io::ErrorKind::InvalidData),
+			Ok((3, 24, "end".into())),
+		]);
+		assert_eq!(lines(LongLine::Truncate, source), vec![
+			Ok((1, 0, "short".into())),
+			Ok((2, 6, "this lin".into())),
+			Ok((3, 24, "end".into())),
+		]);
+		assert_eq!(lines(LongLine::Grow { max_capacity: 32 }, source), vec![
+			Ok((1, 0, "short".into())),
+			Ok((2, 6, "this line is long".into())),
+			Ok((3, 24, "end".into())),
+		]);
+		assert_eq!(lines(LongLine::Grow { max_capacity: 16 }, source), vec![
+			Ok((1, 0, "short".into())),
+			Err(std::io::ErrorKind::InvalidData),
+			Ok((3, 24, "end".into())),
+		]);
+		// terminator isn't counted toward capacity
+		assert_eq!(lines(LongLine::Error, b"12345678\nab\n12345678\r\n"), vec![
+			Ok((1, 0, "12345678".into())),
+			Ok((2, 9, "ab".into())),
+			Ok((3, 12, "12345678".into())),
+		]);
+		assert_eq!(lines(LongLine::Truncate, b"1234567\r\n123456789\nab"), vec![
+			Ok((1, 0, "1234567".into())),
+			Ok((2, 9, "12345678".into())),
+			Ok((3, 19, "ab".into())),
+		]);
+		assert_eq!(lines(LongLine::Error, b"123456789\n123456789"), vec![
+			Err(std::io::ErrorKind::InvalidData),
+			Err(std::io::ErrorKind::InvalidData),
+		]);
+	}
+}
@@ -0,0 +1,180 @@
+//! Windows of [`Frame`](crate::Frame) over memory mapped file, without copying file into buffer
+
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+use std::os::unix::io::AsRawFd;
+
+use crate::{FrameError, Mapped, ReadOutcome, Windowed};
+
+/// Frame compatible view of memory mapped file, window is borrowed from mapping
+///
+/// Fill, consume, preserved region and absolute offsets behave like [`Frame`](crate::Frame),
+/// except fill never block and report end of stream in same call which reach end of file.
+/// File must not be truncated while it's mapped.
+pub struct MmapFrame {
+	ptr: *const u8,
+	// length of mapping, position past last byte frame may expose
+	map_len: usize,
+	end: usize,
+	capacity: usize,
+	preserved: usize,
+	// file position of window
+	start: usize,
+	len: usize,
+}
+
+// mapping is read only and owned by frame
+unsafe impl Send for MmapFrame {}
+unsafe impl Sync for MmapFrame {}
+
+impl MmapFrame {
+	pub fn open(path: impl AsRef<std::path::Path>, capacity: usize, preserved: usize) -> io::Result<Self> {
+		Self::from_file(&File::open(path)?, capacity, preserved)
+	}
+
+	/// Map whole `file` and hint kernel that it will be read sequentially, mapping outlive `file`
+	pub fn from_file(file: &File, capacity: usize, preserved: usize) -> io::Result<Self> {
+		if preserved >= capacity { return Err(FrameError::PreservedTooLarge { capacity, preserved }.into()); }
+		let map_len = usize::try_from(file.metadata()?.len())
+			.map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file is too large to map"))?;
+		let ptr = if map_len == 0 {
+			// empty mapping isn't allowed
+			std::ptr::NonNull::dangling().as_ptr()
+		} else {
+			unsafe {
+				let ptr = libc::mmap(std::ptr::null_mut(), map_len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0);
+				if ptr == libc::MAP_FAILED { return Err(io::Error::last_os_error()); }
+				libc::madvise(ptr, map_len, libc::MADV_SEQUENTIAL);
+				ptr.cast::<u8>()
+			}
+		};
+		Ok(Self { ptr, map_len, end: map_len, capacity, preserved, start: 0, len: 0 })
+	}
+
+	/// Move window to absolute `offset` in file, dropping current window and limit
+	pub fn seek(&mut self, offset: u64) {
+		self.start = usize::try_from(offset).map_or(self.map_len, |offset| offset.min(self.map_len));
+		self.len = 0;
+		self.end = self.map_len;
+	}
+
+	/// Expose only `limit` more bytes past current window, once reached [`MmapFrame::fill`] report end of stream
+	pub fn take(&mut self, limit: u64) {
+		let pos = self.start + self.len;
+		self.end = usize::try_from(limit).map_or(self.map_len, |limit| pos.saturating_add(limit).min(self.map_len));
+	}
+
+	/// Extend window up to capacity, full window still grow like [`Frame::fill`](crate::Frame::fill)
+	pub fn fill(&mut self) -> ReadOutcome {
+		let want = if self.len < self.capacity { self.capacity - self.len } else { self.capacity - self.preserved };
+		let read = want.min(self.end - self.start - self.len);
+		self.len += read;
+		ReadOutcome { read, full: read == want, eof: self.start + self.len == self.end, short: read < want }
+	}
+
+	/// Absolute offset of first byte of current window in file
+	pub fn offset(&self) -> u64 {
+		self.start as u64
+	}
+
+	/// Absolute offset of preserved region, which will be start of window after [`MmapFrame::consume`]
+	pub fn preserved_offset(&self) -> u64 {
+		(self.start + self.len.saturating_sub(self.preserved)) as u64
+	}
+
+	/// Get current slice of data except preserved region with its absolute offset and advance window past it
+	pub fn consume(&mut self) -> (u64, &[u8]) {
+		let n = self.len.saturating_sub(self.preserved);
+		let start = self.start;
+		self.start += n;
+		self.len -= n;
+		(start as u64, unsafe { std::slice::from_raw_parts(self.ptr.add(start), n) })
+	}
+}
+
+impl Windowed for MmapFrame {
+	type Consumed<'a> = &'a [u8];
+
+	fn offset(&self) -> u64 { MmapFrame::offset(self) }
+
+	fn preserved(&self) -> usize { self.preserved }
+
+	fn preserved_offset(&self) -> u64 { MmapFrame::preserved_offset(self) }
+
+	fn consume(&mut self) -> (u64, &[u8]) { MmapFrame::consume(self) }
+}
+
+impl Mapped for MmapFrame {
+	fn fill(&mut self) -> ReadOutcome { MmapFrame::fill(self) }
+}
+
+impl Drop for MmapFrame {
+	fn drop(&mut self) {
+		if self.map_len > 0 { unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.map_len); } }
+	}
+}
+
+impl Deref for MmapFrame {
+	type Target = [u8];
+
+	fn deref(&self) -> &Self::Target {
+		unsafe { std::slice::from_raw_parts(self.ptr.add(self.start), self.len) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::ops::Deref;
+
+	use crate::ReadOutcome;
+
+	use super::MmapFrame;
+
+	#[test]
+	fn test_mmap() {
+		let path = crate::tests::temp_path("mmap");
+		std::fs::write(&path, b"Hello world!").unwrap();
+		let mut frame = MmapFrame::open(&path, 8, 2).unwrap();
+		assert_eq!(frame.fill(), ReadOutcome { read: 8, full: true, eof: false, short: false });
+		assert_eq!(frame.deref(), b"Hello wo");
+		assert_eq!(frame.consume(), (0, &b"Hello "[..]));
+		assert_eq!(frame.preserved_offset(), 6);
+		// last bytes are exposed in same call which report end of file
+		assert_eq!(frame.fill(), ReadOutcome { read: 4, full: false, eof: true, short: true });
+		assert_eq!(frame.deref(), b"world!");
+		assert_eq!(frame.consume(), (6, &b"worl"[..]));
+
+		frame.seek(3);
+		frame.take(5);
+		assert!(frame.fill().eof);
+		assert_eq!(frame.deref(), b"lo wo");
+		assert_eq!(frame.offset(), 3);
+		std::fs::write(&path, b"").unwrap();
+		let mut frame = MmapFrame::open(&path, 8, 2).unwrap();
+		assert_eq!(frame.fill(), ReadOutcome { read: 0, full: false, eof: true, short: true });
+		std::fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	#[cfg(any(feature = "memchr", feature = "regex"))]
+	fn test_mmap_scan() {
+		let path = crate::tests::temp_path("mmap_scan");
+		std::fs::write(&path, b"hello world, hello stream, hellhello").unwrap();
+		#[cfg(feature = "memchr")]
+		{
+			let mut finder = crate::search::Finder::with_window(b"hello", MmapFrame::open(&path, 8, 4).unwrap());
+			let found: Vec<_> = std::iter::from_fn(|| finder.next_mapped()).collect();
+			assert_eq!(found, vec![0, 13, 31]);
+		}
+		#[cfg(feature = "regex")]
+		{
+			let regex = regex::bytes::Regex::new(r"\bhel+o\b").unwrap();
+			let mut finder = crate::regex::RegexFinder::with_window(regex, 5, MmapFrame::open(&path, 8, 6).unwrap());
+			assert_eq!(finder.next_mapped().unwrap(), Some(0..5));
+			assert_eq!(finder.next_mapped().unwrap(), Some(13..18));
+			assert_eq!(finder.next_mapped().unwrap(), None);
+		}
+		std::fs::remove_file(path).unwrap();
+	}
+}
@@ -0,0 +1,118 @@
+//! Multi-pattern search over stream using Aho-Corasick automaton
+
+use std::collections::VecDeque;
+use std::io;
+
+use aho_corasick::{AhoCorasick, BuildError};
+
+#[cfg(feature = "read_std")]
+use crate::BlockingSource;
+use crate::{Frame, Mapped, Source, Windowed};
+use crate::scan::Scanner;
+
+/// Find every occurrence of every pattern in stream read through [`Frame`] or other [`Windowed`] frame
+///
+/// Preserved region is sized to longest pattern minus one, overlapping occurrences are reported too.
+pub struct MultiFinder<W = Frame> {
+	automaton: AhoCorasick,
+	scanner: Scanner<W>,
+	// matches of scanned window which are not returned yet
+	found: VecDeque<(usize, u64)>,
+}
+
+impl MultiFinder {
+	pub fn new<I, P>(patterns: I, capacity: usize) -> Result<Self, BuildError>
+		where I: IntoIterator<Item = P>,
+		      P: AsRef<[u8]> {
+		let automaton = AhoCorasick::new(patterns)?;
+		// empty pattern match at every offset, including end of stream
+		if automaton.min_pattern_len() == 0 { panic!("Pattern must not be empty") }
+		let preserved = automaton.max_pattern_len().saturating_sub(1);
+		if capacity <= preserved { panic!("Capacity must be larger than longest pattern") }
+		Ok(Self::with_automaton(automaton, Frame::raw(capacity, preserved)))
+	}
+}
+
+impl<W: Windowed> MultiFinder<W> {
+	/// Search through already created `window`, its preserved region has to hold longest pattern without last byte
+	pub fn with_window<I, P>(patterns: I, window: W) -> Result<Self, BuildError>
+		where I: IntoIterator<Item = P>,
+		      P: AsRef<[u8]> {
+		let automaton = AhoCorasick::new(patterns)?;
+		if automaton.min_pattern_len() == 0 { panic!("Pattern must not be empty") }
+		if window.preserved() < automaton.max_pattern_len().saturating_sub(1) {
+			panic!("Preserved region must be at least longest pattern minus one byte")
+		}
+		Ok(Self::with_automaton(automaton, window))
+	}
+
+	fn with_automaton(automaton: AhoCorasick, window: W) -> Self {
+		Self { automaton, scanner: Scanner::new(window), found: VecDeque::new() }
+	}
+
+	/// Next match from current window, `None` when window has to be refilled
+	fn find(&mut self) -> Option<Option<(usize, u64)>> {
+		if self.found.is_empty() {
+			let (window, offset, limit) = self.scanner.window();
+			let mut found: Vec<_> = self.automaton.find_overlapping_iter(window)
+				.map(|m| (offset + m.start() as u64, m.pattern().as_usize()))
+				.filter(|&(pos, _)| pos < limit)
+				.collect();
+			found.sort_unstable();
+			self.found.extend(found.into_iter().map(|(pos, id)| (id, pos)));
+			let more = self.scanner.advance();
+			if self.found.is_empty() { return if more { None } else { Some(None) }; }
+		}
+		self.found.pop_front().map(Some)
+	}
+}
+
+impl MultiFinder {
+	/// Next match as pattern index and absolute offset, `None` once source is exhausted
+	pub async fn next<S: Source + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<(usize, u64)>> {
+		loop {
+			if let Some(found) = self.find() { return Ok(found); }
+			self.scanner.fill(source).await?;
+		}
+	}
+
+	/// Blocking version of [`MultiFinder::next`]
+	#[cfg(feature = "read_std")]
+	pub fn next_blocking<S: BlockingSource + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<(usize, u64)>> {
+		loop {
+			if let Some(found) = self.find() { return Ok(found); }
+			self.scanner.fill_blocking(source)?;
+		}
+	}
+}
+
+impl<W: Mapped> MultiFinder<W> {
+	/// Version of [`MultiFinder::next`] for frame which fill itself
+	pub fn next_mapped(&mut self) -> Option<(usize, u64)> {
+		loop {
+			if let Some(found) = self.find() { return found; }
+			self.scanner.fill_mapped();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_multi_blocking() {
+		let mut finder = super::MultiFinder::new(["hello", "lo", "world"], 8).unwrap();
+		let mut haystack = &b"hello world, hello"[..];
+		let mut found = Vec::new();
+		while let Some(m) = finder.next_blocking(&mut haystack).unwrap() {
+			found.push(m);
+		}
+		assert_eq!(found, vec![(0, 0), (1, 3), (2, 6), (0, 13), (1, 16)]);
+	}
+
+	#[test]
+	#[should_panic(expected = "Pattern must not be empty")]
+	fn test_empty_pattern() {
+		let _ = super::MultiFinder::new(["", "ab"], 8);
+	}
+}
@@ -0,0 +1,151 @@
+//! Regex search over stream with bounded match length
+
+use std::io;
+use std::ops::Range;
+
+use regex::bytes::Regex;
+
+#[cfg(feature = "read_std")]
+use crate::BlockingSource;
+use crate::{Frame, Mapped, Source, Windowed};
+use crate::scan::Scanner;
+
+/// Find non-overlapping matches of regex in stream read through [`Frame`] or other [`Windowed`] frame
+///
+/// Caller declare maximum length of match, preserved size is one byte more to give assertions like `\b` context.
+/// Match longer than that can't be found reliably across windows, so it's reported as error.
+pub struct RegexFinder<W = Frame> {
+	regex: Regex,
+	max_len: usize,
+	scanner: Scanner<W>,
+	// end of last reported match, empty match right after it isn't reported like in `Regex::find_iter`
+	last_end: Option<u64>,
+}
+
+impl RegexFinder {
+	pub fn new(regex: Regex, max_len: usize, capacity: usize) -> Self {
+		if capacity <= max_len + 1 { panic!("Capacity must be larger than max_len") }
+		Self::with_window(regex, max_len, Frame::raw(capacity, max_len + 1))
+	}
+}
+
+impl<W: Windowed> RegexFinder<W> {
+	/// Search through already created `window`, its preserved region has to be larger than `max_len`
+	pub fn with_window(regex: Regex, max_len: usize, window: W) -> Self {
+		if window.preserved() <= max_len { panic!("Preserved region must be larger than max_len") }
+		// one byte before scan position is kept, so `\b` and other assertions see what precede window
+		let scanner = Scanner::with_context(window, 1);
+		Self { regex, max_len, scanner, last_end: None }
+	}
+
+	/// Next match in current window, `None` when window has to be refilled
+	fn find(&mut self) -> Option<io::Result<Option<Range<u64>>>> {
+		let (window, mut from, offset, limit) = self.scanner.context_window();
+		// search whole window, so assertions see bytes before scan position
+		let found = loop {
+			let Some(m) = self.regex.find_at(window, from) else { break None };
+			let range = offset + m.start() as u64..offset + m.end() as u64;
+			if range.start >= limit { break None; }
+			if !m.is_empty() || Some(range.start) != self.last_end { break Some(range); }
+			if m.start() == window.len() { break None; }
+			from = m.start() + 1;
+		};
+		let Some(range) = found else {
+			return if self.scanner.advance() { None } else { Some(Ok(None)) };
+		};
+		self.last_end = Some(range.end);
+		self.scanner.skip_to(if range.is_empty() { range.end + 1 } else { range.end });
+		if range.end - range.start > self.max_len as u64 {
+			return Some(Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+				"match at {} is longer than {} bytes", range.start, self.max_len
+			))));
+		}
+		Some(Ok(Some(range)))
+	}
+}
+
+impl RegexFinder {
+	/// Absolute range of next match, `None` once source is exhausted
+	pub async fn next<S: Source + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<Range<u64>>> {
+		loop {
+			if let Some(found) = self.find() { return found; }
+			self.scanner.fill(source).await?;
+		}
+	}
+
+	/// Blocking version of [`RegexFinder::next`]
+	#[cfg(feature = "read_std")]
+	pub fn next_blocking<S: BlockingSource + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<Range<u64>>> {
+		loop {
+			if let Some(found) = self.find() { return found; }
+			self.scanner.fill_blocking(source)?;
+		}
+	}
+}
+
+impl<W: Mapped> RegexFinder<W> {
+	/// Version of [`RegexFinder::next`] for frame which fill itself
+	pub fn next_mapped(&mut self) -> io::Result<Option<Range<u64>>> {
+		loop {
+			if let Some(found) = self.find() { return found; }
+			self.scanner.fill_mapped();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use regex::bytes::Regex;
+
+	use super::RegexFinder;
+
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_regex_blocking() {
+		let mut finder = RegexFinder::new(Regex::new(r"\d+").unwrap(), 4, 8);
+		let mut haystack = &b"ab 12 cd 3456 e 7"[..];
+		let mut found = Vec::new();
+		while let Some(m) = finder.next_blocking(&mut haystack).unwrap() {
+			found.push(m);
+		}
+		assert_eq!(found, vec![3..5, 9..13, 16..17]);
+
+		let mut finder = RegexFinder::new(Regex::new(r"\d+").unwrap(), 2, 8);
+		let mut haystack = &b"ab 12 cd 3456 e 7"[..];
+		assert_eq!(finder.next_blocking(&mut haystack).unwrap(), Some(3..5));
+		let err = finder.next_blocking(&mut haystack).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+	}
+
+	#[cfg(feature = "read_std")]
+	fn find_all(regex: &str, max_len: usize, capacity: usize, mut haystack: &[u8]) -> Vec<std::ops::Range<u64>> {
+		let mut finder = RegexFinder::new(Regex::new(regex).unwrap(), max_len, capacity);
+		let mut found = Vec::new();
+		while let Some(m) = finder.next_blocking(&mut haystack).unwrap() {
+			found.push(m);
+		}
+		found
+	}
+
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_regex_empty_match() {
+		assert_eq!(find_all(r"\d*", 2, 4, b"ab1"), vec![0..0, 1..1, 2..3]);
+		assert_eq!(find_all(r"a*", 3, 5, b"baaab"), vec![0..0, 1..4, 5..5]);
+		assert_eq!(find_all(r"", 1, 3, b"abc"), vec![0..0, 1..1, 2..2, 3..3]);
+	}
+
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_regex_context() {
+		assert_eq!(find_all(r"\b\w", 1, 3, b"ab cd"), vec![0..1, 3..4]);
+		// same matches as searching whole haystack at once, whatever window size is
+		let haystack = b"one two  three,four five";
+		let expected: Vec<_> = Regex::new(r"\b\w+\b").unwrap().find_iter(haystack)
+			.map(|m| m.start() as u64..m.end() as u64)
+			.collect();
+		for capacity in [7, 8, 13, 32] {
+			assert_eq!(find_all(r"\b\w+\b", 5, capacity, haystack), expected);
+		}
+	}
+}
@@ -0,0 +1,196 @@
+//! Mirrored ring buffer backend, window stay contiguous without moving preserved region
+//!
+//! Same memfd pages are mapped twice back to back, so data which wrap around end of ring
+//! can be read as single slice past it.
+
+use std::io;
+use std::ops::Deref;
+
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncRead;
+
+use crate::{FrameError, ReadOutcome};
+
+/// Frame over mirrored ring buffer, consuming only advance start of window
+///
+/// Consumed bytes are borrowed from ring, they are overwritten by next read.
+pub struct RingFrame {
+	ptr: *mut u8,
+	// size of ring, mapping is twice as large
+	size: usize,
+	preserved: usize,
+	// start of window in ring
+	head: usize,
+	len: usize,
+	offset: u64,
+}
+
+// mapping is owned by frame and only accessed through it
+unsafe impl Send for RingFrame {}
+unsafe impl Sync for RingFrame {}
+
+impl RingFrame {
+	/// Map ring of at least `capacity` bytes, it's rounded up to page size
+	pub fn new(capacity: usize, preserved: usize) -> io::Result<Self> {
+		let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+		let size = capacity.max(1).div_ceil(page) * page;
+		if preserved >= size { return Err(FrameError::PreservedTooLarge { capacity: size, preserved }.into()); }
+		let ptr = unsafe { map(size)? };
+		Ok(Self { ptr, size, preserved, head: 0, len: 0, offset: 0 })
+	}
+
+	/// Size of ring after rounding to page size
+	pub fn capacity(&self) -> usize {
+		self.size
+	}
+
+	/// Absolute offset of first byte of current window in stream
+	pub fn offset(&self) -> u64 {
+		self.offset
+	}
+
+	/// Absolute offset of preserved region, which will be start of window after [`RingFrame::consume`]
+	pub fn preserved_offset(&self) -> u64 {
+		self.offset + self.len.saturating_sub(self.preserved) as u64
+	}
+
+	/// Set absolute offset of current window, use when source doesn't start at beginning of stream
+	pub fn set_offset(&mut self, offset: u64) {
+		self.offset = offset;
+	}
+
+	/// Get current slice of data except preserved region with its absolute offset and advance window past it
+	pub fn consume(&mut self) -> (u64, &[u8]) {
+		let n = self.len.saturating_sub(self.preserved);
+		let (start, offset) = (self.head, self.offset);
+		self.head = (self.head + n) % self.size;
+		self.len -= n;
+		self.offset += n as u64;
+		(offset, unsafe { std::slice::from_raw_parts(self.ptr.add(start), n) })
+	}
+
+	/// Free part of ring, it's contiguous thanks to mirror
+	fn spare_mut(&mut self) -> &mut [u8] {
+		unsafe { std::slice::from_raw_parts_mut(self.ptr.add(self.head + self.len), self.size - self.len) }
+	}
+
+	/// Account result of single read which had `spare` bytes of room, return `Some` once fill loop should stop
+	fn filled(&mut self, outcome: &mut ReadOutcome, spare: usize, res: io::Result<usize>) -> Option<io::Result<ReadOutcome>> {
+		match res {
+			Ok(n) => {
+				self.len += n;
+				outcome.read += n;
+				outcome.short = n < spare;
+				outcome.eof = n == 0;
+				if n != 0 && outcome.read < (self.preserved << 1) && self.len < self.size { return None; }
+				outcome.full = self.len == self.size;
+				Some(Ok(*outcome))
+			}
+			Err(err) if err.kind() == io::ErrorKind::Interrupted => None,
+			Err(err) => Some(Err(err)),
+		}
+	}
+
+	/// Read into ring until enough data is buffered, see [`Frame::fill`](crate::Frame::fill)
+	pub fn read_std<R: io::Read>(&mut self, reader: &mut R) -> io::Result<ReadOutcome> {
+		let mut outcome = ReadOutcome::default();
+		loop {
+			// full ring can't take more, empty read would look like end of stream
+			if self.len == self.size { return Ok(ReadOutcome { full: true, ..outcome }); }
+			let spare = self.spare_mut();
+			let len = spare.len();
+			let res = reader.read(spare);
+			if let Some(res) = self.filled(&mut outcome, len, res) { return res; }
+		}
+	}
+
+	#[cfg(feature = "tokio")]
+	pub async fn read_tokio<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> io::Result<ReadOutcome> {
+		use tokio::io::AsyncReadExt;
+		let mut outcome = ReadOutcome::default();
+		loop {
+			if self.len == self.size { return Ok(ReadOutcome { full: true, ..outcome }); }
+			let spare = self.spare_mut();
+			let len = spare.len();
+			let res = reader.read(spare).await;
+			if let Some(res) = self.filled(&mut outcome, len, res) { return res; }
+		}
+	}
+}
+
+/// Reserve address space for two copies of ring and map same memfd into both halves
+unsafe fn map(size: usize) -> io::Result<*mut u8> {
+	let fd = libc::memfd_create(c"framed-stream".as_ptr(), libc::MFD_CLOEXEC);
+	if fd < 0 { return Err(io::Error::last_os_error()); }
+	let res = mirror(fd, size);
+	libc::close(fd);
+	res
+}
+
+unsafe fn mirror(fd: libc::c_int, size: usize) -> io::Result<*mut u8> {
+	if libc::ftruncate(fd, size as libc::off_t) < 0 { return Err(io::Error::last_os_error()); }
+	let base = libc::mmap(std::ptr::null_mut(), size << 1, libc::PROT_NONE, libc::MAP_PRIVATE | libc::MAP_ANONYMOUS, -1, 0);
+	if base == libc::MAP_FAILED { return Err(io::Error::last_os_error()); }
+	for half in [base, base.cast::<u8>().add(size).cast()] {
+		let ptr = libc::mmap(half, size, libc::PROT_READ | libc::PROT_WRITE, libc::MAP_SHARED | libc::MAP_FIXED, fd, 0);
+		if ptr == libc::MAP_FAILED {
+			let err = io::Error::last_os_error();
+			libc::munmap(base, size << 1);
+			return Err(err);
+		}
+	}
+	Ok(base.cast())
+}
+
+impl Drop for RingFrame {
+	fn drop(&mut self) {
+		unsafe { libc::munmap(self.ptr.cast(), self.size << 1); }
+	}
+}
+
+impl Deref for RingFrame {
+	type Target = [u8];
+
+	fn deref(&self) -> &Self::Target {
+		unsafe { std::slice::from_raw_parts(self.ptr.add(self.head), self.len) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::RingFrame;
+
+	#[test]
+	fn test_mirror() {
+		let mut ring = RingFrame::new(1, 0).unwrap();
+		let size = ring.capacity();
+		unsafe { *ring.ptr.add(size - 1) = b'!'; }
+		// same page is visible past end of ring
+		assert_eq!(unsafe { *ring.ptr.add(size * 2 - 1) }, b'!');
+		ring.len = size;
+		assert_eq!(ring.consume().1.len(), size);
+	}
+
+	#[test]
+	fn test_ring_std() {
+		let data: Vec<u8> = (0..20000u32).map(|i| (i % 251) as u8).collect();
+		let mut ring = RingFrame::new(4096, 1024).unwrap();
+		let size = ring.capacity();
+		let mut reader = &data[..];
+		let mut consumed = Vec::new();
+		loop {
+			let outcome = ring.read_std(&mut reader).unwrap();
+			let offset = ring.offset() as usize;
+			// window is contiguous even when it wrap around end of ring
+			assert_eq!(&ring[..], &data[offset..offset + ring.len()]);
+			if outcome.eof { break; }
+			assert!(outcome.full || ring.len() >= 2048);
+			let (offset, bytes) = ring.consume();
+			assert_eq!(offset as usize, consumed.len());
+			consumed.extend_from_slice(bytes);
+			assert!(ring.len() <= 1024 && ring.len() < size);
+		}
+		consumed.extend_from_slice(&ring[..]);
+		assert_eq!(consumed, data);
+	}
+}
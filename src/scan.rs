@@ -0,0 +1,98 @@
+//! Shared driver of matchers which report every match once across overlapping windows
+//!
+//! Preserved region must be at least longest match minus one, then match which start before
+//! preserved region is always complete in current window and match starting inside of it
+//! is left for next window, which start at preserved region.
+
+use std::io;
+
+#[cfg(feature = "read_std")]
+use crate::BlockingSource;
+use crate::{Frame, Mapped, Source, Windowed};
+
+pub(crate) struct Scanner<W = Frame> {
+	frame: W,
+	// matches before this offset are already reported
+	next: u64,
+	// bytes before scan position kept in window for matchers which look behind match start,
+	// they're part of preserved region
+	context: usize,
+	eof: bool,
+}
+
+impl<W: Windowed> Scanner<W> {
+	pub(crate) fn new(frame: W) -> Self {
+		Self { next: frame.offset(), frame, context: 0, eof: false }
+	}
+
+	/// Keep `context` bytes before scan position in next window, frame has to preserve them on top of longest match
+	#[cfg(feature = "regex")]
+	pub(crate) fn with_context(frame: W, context: usize) -> Self {
+		Self { context, ..Self::new(frame) }
+	}
+
+	/// Offset from which matches belong to next window, context bytes before it stay preserved
+	fn boundary(&self) -> u64 {
+		self.frame.offset() + self.frame.len().saturating_sub(self.frame.preserved() - self.context) as u64
+	}
+
+	fn limit(&self) -> u64 {
+		if self.eof { u64::MAX } else { self.boundary() }
+	}
+
+	/// Position of next scan in current window, it's past end of window once empty match at end was reported
+	fn from(&self) -> usize {
+		((self.next - self.frame.offset()) as usize).min(self.frame.len())
+	}
+
+	/// Part of current window which is not scanned yet, its absolute offset
+	/// and offset from which matches belong to next window
+	#[cfg(any(feature = "memchr", feature = "aho-corasick"))]
+	pub(crate) fn window(&self) -> (&[u8], u64, u64) {
+		let from = self.from();
+		(&self.frame[from..], self.frame.offset() + from as u64, self.limit())
+	}
+
+	/// Whole current window with position of next scan in it, its absolute offset
+	/// and offset from which matches belong to next window
+	#[cfg(feature = "regex")]
+	pub(crate) fn context_window(&self) -> (&[u8], usize, u64, u64) {
+		(&self.frame, self.from(), self.frame.offset(), self.limit())
+	}
+
+	/// Mark matches which start before `pos` as reported
+	#[cfg(any(feature = "memchr", feature = "regex"))]
+	pub(crate) fn skip_to(&mut self, pos: u64) {
+		self.next = self.next.max(pos);
+	}
+
+	/// Move to next window, return `false` when stream is exhausted and nothing is left to scan
+	pub(crate) fn advance(&mut self) -> bool {
+		if self.eof {
+			self.next = self.frame.offset() + self.frame.len() as u64;
+			return false;
+		}
+		self.next = self.next.max(self.boundary());
+		if self.frame.len() > self.frame.preserved() { self.frame.consume(); }
+		true
+	}
+}
+
+impl Scanner {
+	pub(crate) async fn fill<S: Source + ?Sized>(&mut self, source: &mut S) -> io::Result<()> {
+		self.eof = self.frame.fill(source).await?.eof;
+		Ok(())
+	}
+
+	#[cfg(feature = "read_std")]
+	pub(crate) fn fill_blocking<S: BlockingSource + ?Sized>(&mut self, source: &mut S) -> io::Result<()> {
+		self.eof = self.frame.fill_blocking(source)?.eof;
+		Ok(())
+	}
+}
+
+impl<W: Mapped> Scanner<W> {
+	pub(crate) fn fill_mapped(&mut self) {
+		self.eof = self.frame.fill().eof;
+	}
+}
@@ -0,0 +1,121 @@
+//! Substring search over stream, occurrences crossing window boundary are found exactly once
+
+use std::io;
+
+use memchr::memmem;
+
+#[cfg(feature = "read_std")]
+use crate::BlockingSource;
+use crate::{Frame, Mapped, Source, Windowed};
+use crate::scan::Scanner;
+
+/// Find every occurrence of needle in stream read through [`Frame`] or other [`Windowed`] frame
+///
+/// Preserved region is sized to `needle.len() - 1`, so occurrence which start in current window
+/// is always complete, occurrence starting in preserved region is left for next window.
+pub struct Finder<'n, W = Frame> {
+	finder: memmem::Finder<'n>,
+	scanner: Scanner<W>,
+}
+
+impl<'n> Finder<'n> {
+	pub fn new(needle: &'n [u8], capacity: usize) -> Self {
+		// empty needle match at every offset, including end of stream
+		if needle.is_empty() { panic!("Needle must not be empty") }
+		let preserved = needle.len() - 1;
+		if capacity <= preserved { panic!("Capacity must be larger than needle") }
+		Self::with_window(needle, Frame::raw(capacity, preserved))
+	}
+}
+
+impl<'n, W: Windowed> Finder<'n, W> {
+	/// Search through already created `window`, its preserved region has to hold needle without last byte
+	pub fn with_window(needle: &'n [u8], window: W) -> Self {
+		if needle.is_empty() { panic!("Needle must not be empty") }
+		if window.preserved() < needle.len() - 1 { panic!("Preserved region must be at least needle minus one byte") }
+		Self { finder: memmem::Finder::new(needle), scanner: Scanner::new(window) }
+	}
+
+	/// Next occurrence in current window, `None` when window has to be refilled
+	fn find(&mut self) -> Option<Option<u64>> {
+		let (window, offset, limit) = self.scanner.window();
+		if let Some(pos) = self.finder.find(window) {
+			let pos = offset + pos as u64;
+			if pos < limit {
+				self.scanner.skip_to(pos + 1);
+				return Some(Some(pos));
+			}
+		}
+		if self.scanner.advance() { None } else { Some(None) }
+	}
+}
+
+impl<'n> Finder<'n> {
+	/// Absolute offset of next occurrence, `None` once source is exhausted
+	pub async fn next<S: Source + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<u64>> {
+		loop {
+			if let Some(found) = self.find() { return Ok(found); }
+			self.scanner.fill(source).await?;
+		}
+	}
+
+	/// Blocking version of [`Finder::next`]
+	#[cfg(feature = "read_std")]
+	pub fn next_blocking<S: BlockingSource + ?Sized>(&mut self, source: &mut S) -> io::Result<Option<u64>> {
+		loop {
+			if let Some(found) = self.find() { return Ok(found); }
+			self.scanner.fill_blocking(source)?;
+		}
+	}
+}
+
+impl<'n, W: Mapped> Finder<'n, W> {
+	/// Version of [`Finder::next`] for frame which fill itself
+	pub fn next_mapped(&mut self) -> Option<u64> {
+		loop {
+			if let Some(found) = self.find() { return found; }
+			self.scanner.fill_mapped();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Finder;
+
+	#[cfg(feature = "read_std")]
+	fn find_all(needle: &[u8], capacity: usize, mut haystack: &[u8]) -> Vec<u64> {
+		let mut finder = Finder::new(needle, capacity);
+		let mut found = Vec::new();
+		while let Some(pos) = finder.next_blocking(&mut haystack).unwrap() {
+			found.push(pos);
+		}
+		found
+	}
+
+	#[test]
+	#[cfg(feature = "read_std")]
+	fn test_find_blocking() {
+		let haystack = b"hello world, hello stream, hellhello";
+		assert_eq!(find_all(b"hello", 8, haystack), vec![0, 13, 31]);
+		assert_eq!(find_all(b"aa", 3, b"aaaa"), vec![0, 1, 2]);
+		assert_eq!(find_all(b"o", 2, haystack), vec![4, 7, 17, 31 + 4]);
+		assert!(find_all(b"missing", 16, haystack).is_empty());
+	}
+
+	#[test]
+	#[should_panic(expected = "Needle must not be empty")]
+	fn test_empty_needle() {
+		Finder::new(b"", 8);
+	}
+
+	#[cfg(feature = "tokio")]
+	#[tokio::test]
+	async fn test_find() {
+		use crate::source::Tokio;
+		let mut finder = Finder::new(b"lo w", 6);
+		let mut source = Tokio(&b"hello world"[..]);
+		assert_eq!(finder.next(&mut source).await.unwrap(), Some(3));
+		assert_eq!(finder.next(&mut source).await.unwrap(), None);
+	}
+}
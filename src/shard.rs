@@ -0,0 +1,204 @@
+//! Scan large file in parallel, one [`Frame`] per range
+//!
+//! Every range is read `preserved` bytes past its end, so match which start inside of range
+//! but cross its end is still found, match is reported only by range it start in.
+
+use std::ops::Range;
+
+use crate::{Frame, FrameBuilder};
+
+/// Split `len` bytes into `count` contiguous ranges of nearly equal size
+pub fn split(len: u64, count: usize) -> Vec<Range<u64>> {
+	let count = count.max(1) as u64;
+	let (size, rem) = (len / count, len % count);
+	let mut start = 0;
+	(0..count).map(|i| {
+		let end = start + size + u64::from(i < rem);
+		let range = start..end;
+		start = end;
+		range
+	}).collect()
+}
+
+/// Matches collected by single range
+struct Shard {
+	own: Range<u64>,
+	// matches before this offset are already reported
+	next: u64,
+	matches: Vec<u64>,
+}
+
+impl Shard {
+	fn new(own: Range<u64>) -> Self {
+		Self { next: own.start, own, matches: Vec::new() }
+	}
+
+	/// Bytes which have to be read to find every match starting in range
+	fn limit(&self, preserved: usize) -> u64 {
+		self.own.end - self.own.start + preserved as u64
+	}
+
+	/// Collect matches of current window, match starting in preserved tail is left for next window
+	fn scan<F, I>(&mut self, frame: &Frame, eof: bool, find: &F)
+		where F: Fn(&[u8]) -> I,
+		      I: IntoIterator<Item = usize> {
+		let start = frame.offset();
+		let end = if eof { self.own.end } else { self.own.end.min(frame.preserved_offset()) };
+		for pos in find(frame) {
+			let pos = start + pos as u64;
+			if pos >= self.next && pos < end { self.matches.push(pos); }
+		}
+		self.next = self.next.max(end);
+	}
+}
+
+/// Collect matches of all shards sorted by offset
+fn merge(shards: impl IntoIterator<Item = Vec<u64>>) -> Vec<u64> {
+	let mut matches: Vec<u64> = shards.into_iter().flatten().collect();
+	matches.sort_unstable();
+	matches
+}
+
+/// Frame of every shard, preserved region only has to hold longest match so ratio to capacity isn't checked
+fn frame(capacity: usize, preserved: usize) -> std::io::Result<Frame> {
+	Ok(FrameBuilder::new(capacity, preserved).max_ratio(0).build()?)
+}
+
+/// Scan file at `path` with `shards` threads, `find` return offsets of matches relative to window it was called with
+///
+/// `preserved` must be at least length of longest match minus one, return absolute offsets of matches
+pub fn scan_std<F, I>(path: impl AsRef<std::path::Path>, shards: usize, capacity: usize, preserved: usize, find: F) -> std::io::Result<Vec<u64>>
+	where F: Fn(&[u8]) -> I + Sync,
+	      I: IntoIterator<Item = usize> {
+	use std::io::{Seek, SeekFrom};
+
+	let path = path.as_ref();
+	let len = std::fs::metadata(path)?.len();
+	let find = &find;
+	std::thread::scope(|scope| {
+		let handles: Vec<_> = split(len, shards).into_iter()
+			.filter(|range| !range.is_empty())
+			.map(|range| scope.spawn(move || {
+				let mut shard = Shard::new(range);
+				let mut file = std::fs::File::open(path)?;
+				file.seek(SeekFrom::Start(shard.own.start))?;
+				let mut frame = frame(capacity, preserved)?;
+				frame.set_offset(shard.own.start);
+				frame.take(shard.limit(preserved));
+				loop {
+					let eof = frame.fill_blocking(&mut file)?.eof;
+					shard.scan(&frame, eof, find);
+					if eof { break Ok(shard.matches); }
+					frame.consume();
+				}
+			}))
+			.collect();
+		handles.into_iter()
+			.map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+			.collect::<std::io::Result<Vec<_>>>()
+			.map(merge)
+	})
+}
+
+/// Tokio version of [`scan_std`], scan every range in its own task
+#[cfg(feature = "tokio")]
+pub async fn scan_tokio<F, I>(path: impl AsRef<std::path::Path>, shards: usize, capacity: usize, preserved: usize, find: F) -> std::io::Result<Vec<u64>>
+	where F: Fn(&[u8]) -> I + Send + Sync + 'static,
+	      I: IntoIterator<Item = usize> {
+	use tokio::io::AsyncSeekExt;
+
+	let path = path.as_ref().to_path_buf();
+	let len = tokio::fs::metadata(&path).await?.len();
+	let find = std::sync::Arc::new(find);
+	let handles: Vec<_> = split(len, shards).into_iter()
+		.filter(|range| !range.is_empty())
+		.map(|range| {
+			let (path, find) = (path.clone(), find.clone());
+			tokio::spawn(async move {
+				let mut shard = Shard::new(range);
+				let mut file = tokio::fs::File::open(path).await?;
+				file.seek(std::io::SeekFrom::Start(shard.own.start)).await?;
+				let mut source = crate::source::Tokio(file);
+				let mut frame = frame(capacity, preserved)?;
+				frame.set_offset(shard.own.start);
+				frame.take(shard.limit(preserved));
+				loop {
+					let eof = frame.fill(&mut source).await?.eof;
+					shard.scan(&frame, eof, &*find);
+					if eof { break std::io::Result::Ok(shard.matches); }
+					frame.consume();
+				}
+			})
+		})
+		.collect();
+	let mut matches = Vec::with_capacity(handles.len());
+	for handle in handles {
+		matches.push(handle.await??);
+	}
+	Ok(merge(matches))
+}
+
+/// Monoio version of [`scan_std`], scan every range in its own task on current thread
+#[cfg(feature = "read_monoio_file")]
+pub async fn scan_monoio<F, I>(path: impl AsRef<std::path::Path>, shards: usize, capacity: usize, preserved: usize, find: F) -> std::io::Result<Vec<u64>>
+	where F: Fn(&[u8]) -> I + 'static,
+	      I: IntoIterator<Item = usize> {
+	let path = path.as_ref().to_path_buf();
+	let len = std::fs::metadata(&path)?.len();
+	let find = std::rc::Rc::new(find);
+	let handles: Vec<_> = split(len, shards).into_iter()
+		.filter(|range| !range.is_empty())
+		.map(|range| {
+			let (path, find) = (path.clone(), find.clone());
+			monoio::spawn(async move {
+				let mut shard = Shard::new(range);
+				let file = monoio::fs::File::open(path).await?;
+				let mut source = crate::source::MonoioFile::range(&file, shard.own.start..shard.own.start + shard.limit(preserved));
+				let mut frame = frame(capacity, preserved)?;
+				frame.set_offset(shard.own.start);
+				loop {
+					let eof = frame.fill(&mut source).await?.eof;
+					shard.scan(&frame, eof, &*find);
+					if eof { break std::io::Result::Ok(shard.matches); }
+					frame.consume();
+				}
+			})
+		})
+		.collect();
+	let mut matches = Vec::with_capacity(handles.len());
+	for handle in handles {
+		matches.push(handle.await?);
+	}
+	Ok(merge(matches))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::split;
+
+	#[test]
+	fn test_split() {
+		assert_eq!(split(10, 3), vec![0..4, 4..7, 7..10]);
+		assert_eq!(split(2, 3), vec![0..1, 1..2, 2..2]);
+		assert_eq!(split(5, 0), vec![0..5]);
+	}
+
+	#[test]
+	fn test_scan_std() {
+		let data: Vec<u8> = (0..1000u32).flat_map(|i| if i % 7 == 0 { b"abc".to_vec() } else { vec![b'a' + (i % 3) as u8 + 1] }).collect();
+		let path = crate::tests::temp_path("shard");
+		std::fs::write(&path, &data).unwrap();
+		let find = |window: &[u8]| window.windows(3).enumerate()
+			.filter(|(_, w)| w == b"abc")
+			.map(|(i, _)| i)
+			.collect::<Vec<_>>();
+		let expected: Vec<u64> = find(&data).into_iter().map(|i| i as u64).collect();
+		for shards in [1, 3, 8] {
+			assert_eq!(super::scan_std(&path, shards, 16, 4, find).unwrap(), expected);
+		}
+		// small preserved region is fine, preserved region as large as capacity is error instead of panic
+		assert_eq!(super::scan_std(&path, 3, 64, 2, find).unwrap(), expected);
+		assert_eq!(super::scan_std(&path, 3, 8, 8, find).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+		std::fs::remove_file(path).unwrap();
+	}
+}
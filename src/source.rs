@@ -0,0 +1,276 @@
+//! Backends which [`Frame`](crate::Frame) can be filled from
+
+use std::future::Future;
+use std::io;
+use std::task::{Context, Poll};
+
+use bytes::BytesMut;
+
+/// Asynchronous source of bytes for [`Frame::fill`](crate::Frame::fill)
+///
+/// Implement this trait to plug new backend into [`Frame`](crate::Frame),
+/// and [`SendSource`] too when its read can be moved between threads
+pub trait Source {
+	/// Append data into spare capacity of `buf` and return number of appended bytes, `0` mean end of stream
+	///
+	/// Implementation must keep existing content of `buf` and should not grow it,
+	/// `Frame` always call this method with some spare capacity available
+	fn read_buf(&mut self, buf: &mut BytesMut) -> impl Future<Output = io::Result<usize>>;
+}
+
+impl<S: Source + ?Sized> Source for &mut S {
+	fn read_buf(&mut self, buf: &mut BytesMut) -> impl Future<Output = io::Result<usize>> {
+		(**self).read_buf(buf)
+	}
+}
+
+/// Source whose read future is `Send`, opt in through [`Sendable`]
+///
+/// Generic code can't tell that read future of [`Source`] is `Send`,
+/// wrap source bounded by this trait into [`Sendable`] to get fill future which can be spawned on multi-threaded runtime
+pub trait SendSource: Send {
+	/// Same as [`Source::read_buf`]
+	fn read_buf(&mut self, buf: &mut BytesMut) -> impl Future<Output = io::Result<usize>> + Send;
+}
+
+impl<S: SendSource + ?Sized> SendSource for &mut S {
+	fn read_buf(&mut self, buf: &mut BytesMut) -> impl Future<Output = io::Result<usize>> + Send {
+		SendSource::read_buf(&mut **self, buf)
+	}
+}
+
+/// Use [`SendSource`] where [`Source`] is expected, keeping its read future `Send`
+pub struct Sendable<S>(pub S);
+
+impl<S: SendSource> Source for Sendable<S> {
+	fn read_buf(&mut self, buf: &mut BytesMut) -> impl Future<Output = io::Result<usize>> {
+		SendSource::read_buf(&mut self.0, buf)
+	}
+}
+
+/// Blocking source of bytes for [`Frame::fill_blocking`](crate::Frame::fill_blocking),
+/// implemented for every [`std::io::Read`]
+#[cfg(feature = "read_std")]
+pub trait BlockingSource {
+	/// Blocking version of [`Source::read_buf`]
+	fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize>;
+}
+
+#[cfg(feature = "read_std")]
+impl<R: io::Read + ?Sized> BlockingSource for R {
+	fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+		let len = buf.len();
+		buf.resize(buf.capacity(), 0);
+		let res = self.read(&mut buf[len..]);
+		buf.truncate(len + res.as_ref().copied().unwrap_or(0));
+		res
+	}
+}
+
+/// Use [`BlockingSource`] where [`Source`] is expected, every read complete immediately
+#[cfg(feature = "read_std")]
+pub struct Blocking<S>(pub S);
+
+#[cfg(feature = "read_std")]
+impl<S: BlockingSource> Source for Blocking<S> {
+	fn read_buf(&mut self, buf: &mut BytesMut) -> impl Future<Output = io::Result<usize>> {
+		std::future::ready(self.0.read_buf(buf))
+	}
+}
+
+#[cfg(feature = "read_std")]
+impl<S: BlockingSource + Send> SendSource for Blocking<S> {
+	fn read_buf(&mut self, buf: &mut BytesMut) -> impl Future<Output = io::Result<usize>> + Send {
+		std::future::ready(self.0.read_buf(buf))
+	}
+}
+
+/// Poll based source, for reads which have to be driven from `poll` of future or stream
+pub trait PollSource {
+	/// Poll version of [`Source::read_buf`]
+	fn poll_read_buf(&mut self, cx: &mut Context<'_>, buf: &mut BytesMut) -> Poll<io::Result<usize>>;
+}
+
+/// Let `read` fill zeroed spare capacity of `buf`, then drop part of it which wasn't read
+///
+/// `futures_io::AsyncRead` can't read into uninitialized memory, so spare capacity is zeroed on every poll
+#[cfg(feature = "futures-io")]
+fn poll_zeroed(buf: &mut BytesMut, read: impl FnOnce(&mut [u8]) -> Poll<io::Result<usize>>) -> Poll<io::Result<usize>> {
+	let len = buf.len();
+	buf.resize(buf.capacity(), 0);
+	let res = read(&mut buf[len..]);
+	// pending or failed read leave buffer untouched
+	match res {
+		Poll::Ready(Ok(n)) => buf.truncate(len + n),
+		_ => buf.truncate(len),
+	}
+	res
+}
+
+/// [`Source`] for [`tokio::io::AsyncRead`]
+#[cfg(feature = "tokio")]
+pub struct Tokio<R>(pub R);
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> Source for Tokio<R> {
+	async fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+		use tokio::io::AsyncReadExt;
+		self.0.read_buf(buf).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin + Send> SendSource for Tokio<R> {
+	async fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+		use tokio::io::AsyncReadExt;
+		self.0.read_buf(buf).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> PollSource for Tokio<R> {
+	fn poll_read_buf(&mut self, cx: &mut Context<'_>, buf: &mut BytesMut) -> Poll<io::Result<usize>> {
+		use bytes::BufMut;
+		// read straight into uninitialized spare capacity like `tokio_util::io::poll_read_buf`
+		let dst = buf.chunk_mut();
+		let dst = unsafe { &mut *(dst as *mut bytes::buf::UninitSlice as *mut [std::mem::MaybeUninit<u8>]) };
+		let mut dst = tokio::io::ReadBuf::uninit(dst);
+		let ptr = dst.filled().as_ptr();
+		let res = std::pin::Pin::new(&mut self.0).poll_read(cx, &mut dst);
+		let Poll::Ready(Ok(())) = res else { return res.map_ok(|()| 0) };
+		// reader must not swap buffer it was given
+		assert_eq!(ptr, dst.filled().as_ptr());
+		let n = dst.filled().len();
+		unsafe { buf.advance_mut(n) };
+		Poll::Ready(Ok(n))
+	}
+}
+
+/// [`Source`] for [`futures_io::AsyncRead`]
+#[cfg(feature = "futures-io")]
+pub struct Futures<R>(pub R);
+
+#[cfg(feature = "futures-io")]
+impl<R: futures_io::AsyncRead + Unpin> Source for Futures<R> {
+	async fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+		std::future::poll_fn(|cx| self.poll_read_buf(cx, buf)).await
+	}
+}
+
+#[cfg(feature = "futures-io")]
+impl<R: futures_io::AsyncRead + Unpin + Send> SendSource for Futures<R> {
+	async fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+		std::future::poll_fn(|cx| self.poll_read_buf(cx, buf)).await
+	}
+}
+
+#[cfg(feature = "futures-io")]
+impl<R: futures_io::AsyncRead + Unpin> PollSource for Futures<R> {
+	fn poll_read_buf(&mut self, cx: &mut Context<'_>, buf: &mut BytesMut) -> Poll<io::Result<usize>> {
+		poll_zeroed(buf, |dst| std::pin::Pin::new(&mut self.0).poll_read(cx, dst))
+	}
+}
+
+/// Lend at most `limit` bytes of spare capacity of `buf` to owned buffer read, then join it back
+///
+/// monoio write from start of buffer, so only spare part of it can be lent
+#[cfg(feature = "monoio")]
+async fn rent<F, Fut>(buf: &mut BytesMut, limit: usize, read: F) -> io::Result<usize>
+	where F: FnOnce(BytesMut) -> Fut,
+	      Fut: Future<Output = monoio::BufResult<usize, BytesMut>> {
+	let mut spare = buf.split_off(buf.len());
+	let rest = spare.split_off(limit.min(spare.capacity()));
+	let (res, spare) = read(spare).await;
+	buf.unsplit(spare);
+	let _ = buf.try_unsplit(rest);
+	res
+}
+
+/// [`Source`] for [`monoio::io::AsyncReadRent`]
+#[cfg(feature = "monoio")]
+pub struct Monoio<R>(pub R);
+
+#[cfg(feature = "monoio")]
+impl<R: monoio::io::AsyncReadRent> Source for Monoio<R> {
+	async fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+		rent(buf, usize::MAX, |spare| self.0.read(spare)).await
+	}
+}
+
+/// [`Source`] for any positional reader implementing [`monoio::io::AsyncReadRentAt`],
+/// read sequentially starting from offset chosen by caller
+#[cfg(feature = "monoio")]
+pub struct Positional<R> {
+	reader: R,
+	pos: u64,
+	end: u64,
+}
+
+#[cfg(feature = "monoio")]
+impl<R> Positional<R> {
+	pub fn new(reader: R, pos: u64) -> Self {
+		Self { reader, pos, end: u64::MAX }
+	}
+
+	/// Read only `range` of `reader`, reaching end of range is reported as end of stream
+	pub fn range(reader: R, range: std::ops::Range<u64>) -> Self {
+		Self { reader, pos: range.start, end: range.end.max(range.start) }
+	}
+
+	/// Offset of next read
+	pub fn position(&self) -> u64 {
+		self.pos
+	}
+
+	pub fn into_inner(self) -> R {
+		self.reader
+	}
+}
+
+#[cfg(feature = "monoio")]
+impl<R: monoio::io::AsyncReadRentAt> Source for Positional<R> {
+	async fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+		let limit = usize::try_from(self.end - self.pos).unwrap_or(usize::MAX);
+		if limit == 0 { return Ok(0); }
+		let pos = usize::try_from(self.pos).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "position out of range"))?;
+		let res = rent(buf, limit, |spare| self.reader.read_at(spare, pos)).await;
+		if let Ok(n) = res { self.pos += n as u64; }
+		res
+	}
+}
+
+/// [`Source`] for [`monoio::fs::File`], read sequentially from `pos`
+#[cfg(feature = "read_monoio_file")]
+pub struct MonoioFile<'a> {
+	file: &'a monoio::fs::File,
+	pos: u64,
+	end: u64,
+}
+
+#[cfg(feature = "read_monoio_file")]
+impl<'a> MonoioFile<'a> {
+	pub fn new(file: &'a monoio::fs::File, pos: u64) -> Self {
+		Self { file, pos, end: u64::MAX }
+	}
+
+	/// Read only `range` of `file`, reaching end of range is reported as end of stream
+	pub fn range(file: &'a monoio::fs::File, range: std::ops::Range<u64>) -> Self {
+		Self { file, pos: range.start, end: range.end.max(range.start) }
+	}
+
+	/// Offset of next read
+	pub fn position(&self) -> u64 {
+		self.pos
+	}
+}
+
+#[cfg(feature = "read_monoio_file")]
+impl Source for MonoioFile<'_> {
+	async fn read_buf(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+		let limit = usize::try_from(self.end - self.pos).unwrap_or(usize::MAX);
+		if limit == 0 { return Ok(0); }
+		let res = rent(buf, limit, |spare| self.file.read_at(spare, self.pos)).await;
+		if let Ok(n) = res { self.pos += n as u64; }
+		res
+	}
+}
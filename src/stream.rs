@@ -0,0 +1,91 @@
+//! [`Stream`] of consumed windows of [`Frame`]
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::{Frame, ReadOutcome, Window};
+use crate::source::PollSource;
+
+/// Fill [`Frame`] from poll based reader and yield consumed [`Window`]s,
+/// last window contain everything left in frame when reader reach end of stream
+pub struct FrameStream<R> {
+	frame: Frame,
+	reader: R,
+	// outcome and number of reads of fill in progress, `None` when next poll start new fill
+	filling: Option<(ReadOutcome, usize)>,
+	done: bool,
+}
+
+impl<R: PollSource + Unpin> FrameStream<R> {
+	pub fn new(frame: Frame, reader: R) -> Self {
+		Self { frame, reader, filling: None, done: false }
+	}
+
+	pub fn into_inner(self) -> (Frame, R) {
+		(self.frame, self.reader)
+	}
+
+	/// Poll version of [`Frame::fill`], progress is kept in frame between polls
+	fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<ReadOutcome>> {
+		let (outcome, reads) = self.filling.get_or_insert_with(|| {
+			self.frame.reserve();
+			(ReadOutcome::default(), 0)
+		});
+		let Poll::Ready(res) = self.frame.poll_fill(cx, &mut self.reader, outcome, reads) else { return Poll::Pending };
+		self.filling = None;
+		Poll::Ready(res)
+	}
+}
+
+impl<R: PollSource + Unpin> Stream for FrameStream<R> {
+	type Item = io::Result<Window>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		if this.done { return Poll::Ready(None); }
+		loop {
+			let outcome = match this.poll_fill(cx) {
+				Poll::Pending => return Poll::Pending,
+				Poll::Ready(Ok(outcome)) => outcome,
+				Poll::Ready(Err(err)) => {
+					this.done = true;
+					return Poll::Ready(Some(Err(err)));
+				}
+			};
+			if outcome.eof { break; }
+			if let Some(window) = this.frame.window() { return Poll::Ready(Some(Ok(window))); }
+		}
+		this.done = true;
+		Poll::Ready(this.frame.last_window().map(Ok))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[cfg(feature = "tokio")]
+	#[tokio::test]
+	async fn test_stream() {
+		use std::pin::Pin;
+
+		use futures_core::Stream;
+
+		use crate::Frame;
+		use crate::source::Tokio;
+
+		let mut stream = super::FrameStream::new(Frame::new(8, 2), Tokio(&b"Hello world!"[..]));
+		let mut windows = Vec::new();
+		while let Some(window) = std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+			let window = window.unwrap();
+			assert_eq!(window.preserved_offset(), window.offset + window.consumed.len() as u64);
+			windows.push((window.offset, window.consumed.to_vec(), window.preserved.to_vec()));
+		}
+		assert_eq!(windows, vec![
+			(0, b"Hello ".to_vec(), b"wo".to_vec()),
+			(6, b"worl".to_vec(), b"d!".to_vec()),
+			(10, b"d!".to_vec(), b"".to_vec()),
+		]);
+	}
+}